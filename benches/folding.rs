@@ -0,0 +1,399 @@
+use std::{
+    collections::HashMap,
+    fs,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nova_scotia::{
+    circom::{circuit::CircomCircuit, reader::load_r1cs},
+    create_public_params, FileLocation, C1, C2, F,
+};
+use nova_scotia_practice::{
+    compression::{CompressionBackend, CompressionKeys},
+    native_circuits::{adder_step_circuits, ThreeInputsCircuit, TwoInputsCircuit},
+    pipeline::{fold, generate_circuits_sequential, StepCircuits},
+    unroll::{unroll, Unrolled},
+    witness::WitnessGenerator,
+};
+use nova_snark::{
+    provider,
+    traits::{
+        circuit::{StepCircuit, TrivialTestCircuit},
+        Group,
+    },
+    PublicParams,
+};
+use serde_json::{json, Value};
+
+/*
+Sweeps every circuit in circuits/ over the curve cycles it was compiled for. circom bakes the prime
+into the r1cs, so only 2inputs has a pasta build.
+
+Raw samples are written as CSV to
+target/criterion/<group>/<cycle>_<circuit>/<iterations>/new/raw.csv and the compressed proof
+sizes of both backends to target/criterion/compressed_proof_size_<cycle>.csv
+*/
+const ITERATION_COUNTS: [usize; 3] = [1, 4, 16];
+
+type Bn254 = provider::bn256_grumpkin::bn256::Point;
+type Grumpkin = provider::bn256_grumpkin::grumpkin::Point;
+
+struct Circuit {
+    name: &'static str,
+    r1cs: &'static str,
+    wasm: &'static str,
+    start_public_input: &'static [u64],
+    private_input: &'static [(&'static str, u64)],
+}
+
+const BN254_CIRCUITS: [Circuit; 4] = [
+    Circuit {
+        name: "2inputs",
+        r1cs: "circuits/2inputs/bn254/bn254.r1cs",
+        wasm: "circuits/2inputs/bn254/bn254.wasm",
+        start_public_input: &[10, 10],
+        private_input: &[("adder", 3)],
+    },
+    Circuit {
+        name: "3inputs",
+        r1cs: "circuits/3inputs/bn254/3inputs.r1cs",
+        wasm: "circuits/3inputs/bn254/3inputs.wasm",
+        start_public_input: &[4, 7, 8],
+        private_input: &[("adder", 9)],
+    },
+    Circuit {
+        name: "poseidon",
+        r1cs: "circuits/poseidon/poseidon_hash.r1cs",
+        wasm: "circuits/poseidon/poseidon_hash.wasm",
+        start_public_input: &[5, 77],
+        private_input: &[("priv_hash_input", 9)],
+    },
+    Circuit {
+        name: "poseidon_2_priv_inputs",
+        r1cs: "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.r1cs",
+        wasm: "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.wasm",
+        start_public_input: &[5],
+        private_input: &[("priv_hash_1", 77), ("priv_hash_2", 9)],
+    },
+];
+
+const PASTA_CIRCUITS: [Circuit; 1] = [Circuit {
+    name: "2inputs",
+    r1cs: "circuits/2inputs/pasta/pasta.r1cs",
+    wasm: "circuits/2inputs/pasta/pasta.wasm",
+    start_public_input: &[10, 10],
+    private_input: &[("adder", 3)],
+}];
+
+/// Returns the `circuit,backend,iterations,bytes` rows of the compressed proof sizes.
+fn bench_circuit<G1, G2>(c: &mut Criterion, cycle: &str, circuit: &Circuit) -> Vec<String>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    let id = format!("{}_{}", cycle, circuit.name);
+    let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(circuit.r1cs.into()));
+    let witness_generator = WitnessGenerator::from_path(circuit.wasm);
+    let start_public_input: Vec<F<G1>> = circuit
+        .start_public_input
+        .iter()
+        .map(|&x| F::<G1>::from(x))
+        .collect();
+    let private_input: HashMap<String, Value> = circuit
+        .private_input
+        .iter()
+        .map(|&(name, value)| (name.to_string(), json!(value)))
+        .collect();
+    let z0_secondary = vec![F::<G2>::from(0)];
+
+    let mut group = c.benchmark_group("public_params");
+    group.sample_size(10);
+    group.bench_function(&id, |b| {
+        b.iter(|| create_public_params::<G1, G2>(r1cs.clone()))
+    });
+    group.finish();
+
+    let pp = create_public_params::<G1, G2>(r1cs.clone());
+
+    let mut group = c.benchmark_group("compressed_setup");
+    group.sample_size(10);
+    for backend in CompressionBackend::ALL {
+        group.bench_function(format!("{}_{:?}", id, backend), |b| {
+            b.iter(|| CompressionKeys::setup(backend, &pp).unwrap())
+        });
+    }
+    group.finish();
+
+    let keys: Vec<_> = CompressionBackend::ALL
+        .iter()
+        .map(|&backend| CompressionKeys::setup(backend, &pp).unwrap())
+        .collect();
+    let mut proof_sizes = Vec::new();
+
+    for iteration_count in ITERATION_COUNTS {
+        let private_inputs = vec![private_input.clone(); iteration_count];
+        let step_circuits = generate_circuits_sequential(
+            &witness_generator,
+            &r1cs,
+            &private_inputs,
+            &start_public_input,
+        );
+
+        // Witnesses are generated above, this only measures folding. The throughput makes
+        // criterion report the time per step as well.
+        let mut group = c.benchmark_group("fold");
+        group.sample_size(10);
+        group.throughput(Throughput::Elements(iteration_count as u64));
+        group.bench_with_input(
+            BenchmarkId::new(&id, iteration_count),
+            &step_circuits,
+            |b, step_circuits| {
+                b.iter(|| fold(&pp, step_circuits, &start_public_input, &z0_secondary).unwrap())
+            },
+        );
+        group.finish();
+
+        let (recursive_snark, _) =
+            fold(&pp, &step_circuits, &start_public_input, &z0_secondary).unwrap();
+
+        let mut group = c.benchmark_group("recursive_verify");
+        group.bench_with_input(
+            BenchmarkId::new(&id, iteration_count),
+            &recursive_snark,
+            |b, recursive_snark| {
+                b.iter(|| {
+                    recursive_snark
+                        .verify(&pp, iteration_count, &start_public_input, &z0_secondary)
+                        .unwrap()
+                })
+            },
+        );
+        group.finish();
+
+        for keys in &keys {
+            let id = format!("{}_{:?}", id, keys.backend());
+
+            let mut group = c.benchmark_group("compressed_prove");
+            group.sample_size(10);
+            group.bench_with_input(
+                BenchmarkId::new(&id, iteration_count),
+                &recursive_snark,
+                |b, recursive_snark| b.iter(|| keys.prove(&pp, recursive_snark).unwrap()),
+            );
+            group.finish();
+
+            let compressed_snark = keys.prove(&pp, &recursive_snark).unwrap();
+
+            let mut group = c.benchmark_group("compressed_verify");
+            group.bench_with_input(
+                BenchmarkId::new(&id, iteration_count),
+                &compressed_snark,
+                |b, compressed_snark| {
+                    b.iter(|| {
+                        compressed_snark
+                            .verify(
+                                keys,
+                                iteration_count,
+                                start_public_input.clone(),
+                                z0_secondary.clone(),
+                            )
+                            .unwrap()
+                    })
+                },
+            );
+            group.finish();
+
+            proof_sizes.push(format!(
+                "{},{:?},{},{}",
+                circuit.name,
+                keys.backend(),
+                iteration_count,
+                compressed_snark.serialized_size()
+            ));
+        }
+    }
+
+    proof_sizes
+}
+
+fn write_proof_sizes(cycle: &str, proof_sizes: Vec<String>) {
+    let mut csv = "circuit,backend,iterations,bytes\n".to_string();
+    for row in proof_sizes {
+        csv.push_str(&row);
+        csv.push('\n');
+    }
+
+    fs::create_dir_all("target/criterion").unwrap();
+    fs::write(
+        format!("target/criterion/compressed_proof_size_{}.csv", cycle),
+        csv,
+    )
+    .unwrap();
+}
+
+fn bench_bn254(c: &mut Criterion) {
+    let mut proof_sizes = Vec::new();
+    for circuit in &BN254_CIRCUITS {
+        proof_sizes.extend(bench_circuit::<Bn254, Grumpkin>(c, "bn254", circuit));
+    }
+    write_proof_sizes("bn254", proof_sizes);
+
+    bench_native::<TwoInputsCircuit<F<Bn254>>>(c, &BN254_CIRCUITS[0]);
+    bench_native::<ThreeInputsCircuit<F<Bn254>>>(c, &BN254_CIRCUITS[1]);
+
+    bench_unrolling(c, &BN254_CIRCUITS[0]);
+    bench_unrolling(c, &BN254_CIRCUITS[1]);
+}
+
+/*
+Proves UNROLLED_STEPS logical steps of a circom circuit with every unroll factor k, i.e. folds
+UNROLLED_STEPS / k iterations of k steps each and compresses the result with Spartan. Setup and
+witness generation are excluded. Besides the criterion group, the best of a few runs per k is
+written to target/criterion/unrolling_<circuit>.csv and the fastest k is printed.
+*/
+const UNROLLED_STEPS: usize = 16;
+const UNROLL_FACTORS: [usize; 5] = [1, 2, 4, 8, 16];
+
+fn bench_unrolling(c: &mut Criterion, circuit: &Circuit) {
+    let id = format!("bn254_{}", circuit.name);
+    let r1cs = load_r1cs::<Bn254, Grumpkin>(&FileLocation::PathBuf(circuit.r1cs.into()));
+    let start_public_input: Vec<F<Bn254>> = circuit
+        .start_public_input
+        .iter()
+        .map(|&x| F::<Bn254>::from(x))
+        .collect();
+    let private_input: HashMap<String, Value> = circuit
+        .private_input
+        .iter()
+        .map(|&(name, value)| (name.to_string(), json!(value)))
+        .collect();
+    let z0_secondary = vec![F::<Grumpkin>::from(0)];
+
+    let step_circuits = generate_circuits_sequential(
+        &WitnessGenerator::from_path(circuit.wasm),
+        &r1cs,
+        &vec![private_input; UNROLLED_STEPS],
+        &start_public_input,
+    );
+
+    let mut csv = "k,iterations,constraints_per_iteration,prove_us\n".to_string();
+    let mut best: Option<(usize, Duration)> = None;
+    for k in UNROLL_FACTORS {
+        let setup_circuit = CircomCircuit {
+            r1cs: r1cs.clone(),
+            witness: None,
+        };
+        let pp = PublicParams::<Bn254, Grumpkin, Unrolled<C1<Bn254>>, C2<Grumpkin>>::setup(
+            &Unrolled::setup_circuit(setup_circuit, k),
+            &TrivialTestCircuit::default(),
+        );
+        let keys = CompressionKeys::setup(CompressionBackend::Spartan, &pp).unwrap();
+        let unrolled = unroll(
+            StepCircuits {
+                circuits: step_circuits.circuits.clone(),
+                witness_times: step_circuits.witness_times.clone(),
+            },
+            k,
+        );
+
+        let prove = || {
+            let (recursive_snark, _) =
+                fold(&pp, &unrolled, &start_public_input, &z0_secondary).unwrap();
+            keys.prove(&pp, &recursive_snark).unwrap()
+        };
+
+        let mut group = c.benchmark_group("unrolled_prove");
+        group.sample_size(10);
+        group.bench_function(BenchmarkId::new(&id, k), |b| b.iter(prove));
+        group.finish();
+
+        let prove_time = (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                prove();
+                start.elapsed()
+            })
+            .min()
+            .unwrap();
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            k,
+            UNROLLED_STEPS / k,
+            pp.num_constraints().0,
+            prove_time.as_micros()
+        ));
+        if best.map_or(true, |(_, time)| prove_time < time) {
+            best = Some((k, prove_time));
+        }
+    }
+
+    let (k, time) = best.unwrap();
+    println!(
+        "{}: proving {} steps is fastest with k = {} ({:?})",
+        id, UNROLLED_STEPS, k, time
+    );
+    fs::create_dir_all("target/criterion").unwrap();
+    fs::write(
+        format!("target/criterion/unrolling_{}.csv", circuit.name),
+        csv,
+    )
+    .unwrap();
+}
+
+/// Folds the Rust versions of 2inputs and 3inputs into the same "fold" group as the circom ones,
+/// as bn254_<circuit>_native, so both show up side by side in the report.
+fn bench_native<C>(c: &mut Criterion, circuit: &Circuit)
+where
+    C: StepCircuit<F<Bn254>> + From<F<Bn254>> + Default,
+{
+    let id = format!("bn254_{}_native", circuit.name);
+    let start_public_input: Vec<F<Bn254>> = circuit
+        .start_public_input
+        .iter()
+        .map(|&x| F::<Bn254>::from(x))
+        .collect();
+    let private_input: HashMap<String, Value> = circuit
+        .private_input
+        .iter()
+        .map(|&(name, value)| (name.to_string(), json!(value)))
+        .collect();
+    let z0_secondary = vec![F::<Grumpkin>::from(0)];
+
+    let pp = PublicParams::<Bn254, Grumpkin, C, C2<Grumpkin>>::setup(
+        &C::default(),
+        &TrivialTestCircuit::default(),
+    );
+    println!("{}: {} constraints per step", id, pp.num_constraints().0);
+
+    for iteration_count in ITERATION_COUNTS {
+        let step_circuits =
+            adder_step_circuits::<F<Bn254>, C>(&vec![private_input.clone(); iteration_count]);
+
+        let mut group = c.benchmark_group("fold");
+        group.sample_size(10);
+        group.throughput(Throughput::Elements(iteration_count as u64));
+        group.bench_with_input(
+            BenchmarkId::new(&id, iteration_count),
+            &step_circuits,
+            |b, step_circuits| {
+                b.iter(|| fold(&pp, step_circuits, &start_public_input, &z0_secondary).unwrap())
+            },
+        );
+        group.finish();
+    }
+}
+
+fn bench_pasta(c: &mut Criterion) {
+    type G1 = pasta_curves::vesta::Point;
+    type G2 = pasta_curves::pallas::Point;
+
+    let mut proof_sizes = Vec::new();
+    for circuit in &PASTA_CIRCUITS {
+        proof_sizes.extend(bench_circuit::<G1, G2>(c, "pasta", circuit));
+    }
+    write_proof_sizes("pasta", proof_sizes);
+}
+
+criterion_group!(benches, bench_bn254, bench_pasta);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+pragma circom 2.1.9;
+
+template Example() {
+    signal input step_in[2];
+
+    signal output step_out[2];
+
+    signal input adders[3];
+    signal input matrix[2][2];
+
+    step_out[0] <== step_in[0] + adders[0] + adders[1] + adders[2];
+    step_out[1] <== step_in[1] + matrix[0][0] + matrix[0][1] + matrix[1][0] + matrix[1][1];
+}
+
+component main { public [ step_in ] } = Example();
@@ -0,0 +1,96 @@
+use ark_ed_on_bn254::{EdwardsAffine, Fq};
+use ark_ff::{BigInteger, PrimeField as ArkPrimeField};
+use ff::PrimeField;
+use nova_scotia::F;
+use nova_snark::provider;
+
+/*
+Baby Jubjub (ark-ed-on-bn254) is defined over the BN254 scalar field, so its base field Fq is the
+same field as F<bn256::Point>, just with a different representation. Both serialize to 32 little
+endian bytes, the conversions go through those bytes and never reduce anything.
+
+This lets values computed with arkworks (hashes, EdDSA signatures, curve points) be fed to the
+bn254 circuits as private inputs or start state.
+*/
+pub type Fr = F<provider::bn256_grumpkin::bn256::Point>;
+
+pub fn ark_to_nova(x: &Fq) -> Fr {
+    let mut repr = <Fr as PrimeField>::Repr::default();
+    repr.as_mut()
+        .copy_from_slice(&x.into_bigint().to_bytes_le());
+
+    // Fq is canonical, so it is always below the (shared) modulus.
+    Fr::from_repr(repr).unwrap()
+}
+
+pub fn nova_to_ark(x: &Fr) -> Fq {
+    Fq::from_le_bytes_mod_order(x.to_repr().as_ref())
+}
+
+/// The affine coordinates [x, y] of a Baby Jubjub point.
+pub fn point_to_nova(p: &EdwardsAffine) -> [Fr; 2] {
+    [ark_to_nova(&p.x), ark_to_nova(&p.y)]
+}
+
+/// Returns `None` when [x, y] is not on the curve or not in the prime order subgroup.
+pub fn point_from_nova(p: &[Fr; 2]) -> Option<EdwardsAffine> {
+    let point = EdwardsAffine::new_unchecked(nova_to_ark(&p[0]), nova_to_ark(&p[1]));
+
+    (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ec::AffineRepr;
+    use ark_ff::{Field, UniformRand};
+    use ff::Field as _;
+    use proptest::test_runner::{RngAlgorithm, TestRng};
+
+    use crate::field;
+
+    #[test]
+    fn same_modulus() {
+        assert_eq!(
+            field::modulus::<Fr>(),
+            num_bigint::BigUint::from_bytes_le(&Fq::MODULUS.to_bytes_le())
+        );
+    }
+
+    #[test]
+    fn field_round_trip() {
+        let mut rng = TestRng::deterministic_rng(RngAlgorithm::ChaCha);
+        for _ in 0..32 {
+            let x = Fq::rand(&mut rng);
+            assert_eq!(nova_to_ark(&ark_to_nova(&x)), x);
+
+            let y = Fr::random(&mut rng);
+            assert_eq!(ark_to_nova(&nova_to_ark(&y)), y);
+        }
+
+        assert_eq!(ark_to_nova(&Fq::ZERO), Fr::ZERO);
+        assert_eq!(ark_to_nova(&-Fq::ONE), -Fr::ONE);
+    }
+
+    #[test]
+    fn arithmetic_is_preserved() {
+        let mut rng = TestRng::deterministic_rng(RngAlgorithm::ChaCha);
+        let x = Fq::rand(&mut rng);
+        let y = Fq::rand(&mut rng);
+
+        assert_eq!(ark_to_nova(&(x * y)), ark_to_nova(&x) * ark_to_nova(&y));
+        assert_eq!(ark_to_nova(&(x + y)), ark_to_nova(&x) + ark_to_nova(&y));
+        assert_eq!(
+            ark_to_nova(&x.inverse().unwrap()),
+            ark_to_nova(&x).invert().unwrap()
+        );
+    }
+
+    #[test]
+    fn point_round_trip() {
+        let generator = EdwardsAffine::generator();
+
+        assert_eq!(point_from_nova(&point_to_nova(&generator)), Some(generator));
+        assert_eq!(point_from_nova(&[Fr::from(1), Fr::from(2)]), None);
+    }
+}
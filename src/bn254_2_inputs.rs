@@ -1,13 +1,23 @@
 use std::{collections::HashMap, env::current_dir, time::Instant};
 
 // Consider nova scotia as some middleware, that will make it easy for you to interact with nova.
-use nova_scotia::{
-    circom::reader::load_r1cs, create_public_params, create_recursive_circuit, FileLocation, F, S,
+use nova_scotia::{circom::reader::load_r1cs, create_public_params, FileLocation, F};
+use nova_snark::{provider, PublicParams};
+
+use crate::{
+    compression::{CompressionBackend, CompressionKeys},
+    field,
+    pipeline::{create_recursive_circuit, WitnessGeneration},
+    report::{serialized_size, ProofSizes, RunReport},
+    witness::WitnessGenerator,
 };
-use nova_snark::{provider, CompressedSNARK, PublicParams};
-use serde_json::json;
 
-pub fn run_bn254_2inputs(circuit_filepath: String, witness_gen_filepath: String) {
+pub fn run_bn254_2inputs(
+    circuit_filepath: String,
+    witness_gen_filepath: String,
+    z0_secondary: Vec<F<provider::bn256_grumpkin::grumpkin::Point>>,
+    backend: CompressionBackend,
+) -> RunReport<provider::bn256_grumpkin::bn256::Point, provider::bn256_grumpkin::grumpkin::Point> {
     /*
     1. Define the curve cycle that we want to use. We will use the bn256/grumpkin curve cycle.
     */
@@ -26,7 +36,7 @@ pub fn run_bn254_2inputs(circuit_filepath: String, witness_gen_filepath: String)
     let root = current_dir().unwrap();
     let circuit_file = root.join(circuit_filepath);
     let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(circuit_file));
-    let witness_generator_file = root.join(witness_gen_filepath);
+    let witness_generator = WitnessGenerator::from_path(root.join(witness_gen_filepath));
 
     /*
     3. Setuping the private auxiliary inputs that we will use when folding. They are two public
@@ -50,12 +60,14 @@ pub fn run_bn254_2inputs(circuit_filepath: String, witness_gen_filepath: String)
 
     for _ in 0..iteration_count {
         let mut private_input = HashMap::new();
-        private_input.insert("adder".to_string(), json!(adder));
+        private_input.insert("adder".to_string(), field::to_json(&F::<G1>::from(adder)));
         private_inputs.push(private_input);
     }
 
     /*
-    4. Set the starting public inputs that we are going to use. (step_in[0], step_in[1])
+    4. Set the starting public inputs that we are going to use. (step_in[0], step_in[1]).
+       z0_secondary, the starting input of the secondary circuit (the trivial circuit running
+       on the other curve of the cycle), is given by the caller.
     */
     let start_public_input = [F::<G1>::from(10), F::<G1>::from(10)];
 
@@ -92,44 +104,54 @@ pub fn run_bn254_2inputs(circuit_filepath: String, witness_gen_filepath: String)
     */
     println!("Creating a RecursiveSNARK...");
     let start = Instant::now();
-    let recursive_snark = create_recursive_circuit(
-        FileLocation::PathBuf(witness_generator_file.clone()),
+    let (recursive_snark, trace) = create_recursive_circuit(
+        &witness_generator,
         r1cs.clone(),
         private_inputs,
         start_public_input.to_vec(),
+        z0_secondary.clone(),
         &pp,
+        WitnessGeneration::Sequential,
     )
     .unwrap();
     println!("RecursiveSNARK creation took {:?}", start.elapsed());
+    println!("Per step profile:\n{}", trace.to_csv());
 
     /*
     8. Verify it
     */
-    let z0_secondary = [F::<G2>::from(0)];
-
     // Verify the recursive SNARK
     println!("Verifying a RecursiveSNARK...");
     let start = Instant::now();
     let res = recursive_snark.verify(&pp, iteration_count, &start_public_input, &z0_secondary);
     println!(
         "RecursiveSNARK::verify: {:?}, took {:?}",
-        res,
+        res.is_ok(),
         start.elapsed()
     );
     assert!(res.is_ok());
 
-    let z_last = res.unwrap().0;
+    let (z_last, z_last_secondary) = res.unwrap();
+    println!(
+        "z_n: {}, z_n of the secondary circuit: {}",
+        field::to_decimal_list(&z_last),
+        field::to_decimal_list(&z_last_secondary)
+    );
 
     assert_eq!(z_last[0], F::<G1>::from(25));
     assert_eq!(z_last[1], F::<G1>::from(90));
 
     /*
-    9. The proof is quite large... so we will compress it using SPARTAN.
+    9. The proof is quite large... so we will compress it using SPARTAN. The preprocessing variant
+       of Spartan takes longer to prove but is much faster to verify.
     */
-    println!("Generating a CompressedSNARK using Spartan with IPA-PC...");
+    println!(
+        "Generating a CompressedSNARK using {:?} with IPA-PC...",
+        backend
+    );
     let start = Instant::now();
-    let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
-    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(&pp, &pk, &recursive_snark);
+    let keys = CompressionKeys::setup(backend, &pp).unwrap();
+    let res = keys.prove(&pp, &recursive_snark);
     println!(
         "CompressedSNARK::prove: {:?}, took {:?}",
         res.is_ok(),
@@ -144,7 +166,7 @@ pub fn run_bn254_2inputs(circuit_filepath: String, witness_gen_filepath: String)
     println!("Verifying a CompressedSNARK...");
     let start = Instant::now();
     let res = compressed_snark.verify(
-        &vk,
+        &keys,
         iteration_count,
         start_public_input.to_vec(),
         z0_secondary.to_vec(),
@@ -156,12 +178,28 @@ pub fn run_bn254_2inputs(circuit_filepath: String, witness_gen_filepath: String)
     );
     assert!(res.is_ok());
 
+    /*
+    11. Look at how many bytes each piece takes once serialized.
+    */
+    let sizes = ProofSizes {
+        public_params: serialized_size(&pp),
+        recursive_snark: serialized_size(&recursive_snark),
+        compressed_snark: compressed_snark.serialized_size(),
+        prover_key: keys.prover_key_size(),
+        verifier_key: keys.verifier_key_size(),
+    };
+    println!("{}", sizes);
+
     /*
     Ensure that you get the following output in your terminal
-    RecursiveSNARK::verify: Ok(([
-        0x0000000000000000000000000000000000000000000000000000000000000019,
-        0x000000000000000000000000000000000000000000000000000000000000005a],
-        [0x0000000000000000000000000000000000000000000000000000000000000000]
-    ))
+    RecursiveSNARK::verify: true, took ...
+    z_n: [25, 90], z_n of the secondary circuit: [0]
     */
+
+    RunReport {
+        z_last,
+        z_last_secondary,
+        trace,
+        sizes,
+    }
 }
@@ -0,0 +1,136 @@
+use ff::PrimeField;
+use nova_scotia::circom::circuit::R1CS;
+
+/*
+Customizable Constraint Systems (CCS, Setty, Thaler and Wahby), the constraint system HyperNova
+folds. A CCS instance is t matrices M_i of size m x n, q multisets S_j of matrix indices and q
+constants c_j, satisfied by z when
+
+    sum_j c_j · ∘_{i in S_j} M_i z = 0
+
+where ∘ is the entry by entry product. R1CS is the special case
+
+    M = (A, B, C),  S = ({0, 1}, {2}),  c = (1, -1)
+
+so Az ∘ Bz - Cz = 0. The paper orders z as (w, 1, x), here z keeps circom's layout
+[1, public signals..., private signals...] so the same witnesses satisfy both forms.
+*/
+
+/// A sparse matrix, every row holding its (column, value) entries, the layout of the A, B and C of
+/// nova-scotia's R1CS.
+pub type SparseMatrix<Fr> = Vec<Vec<(usize, Fr)>>;
+
+#[derive(Clone, Debug)]
+pub struct Ccs<Fr: PrimeField> {
+    /// Number of constraints, the rows of every matrix.
+    pub m: usize,
+    /// Number of variables, the columns of every matrix.
+    pub n: usize,
+    /// Number of public signals, without the constant one.
+    pub l: usize,
+    pub matrices: Vec<SparseMatrix<Fr>>,
+    pub multisets: Vec<Vec<usize>>,
+    pub constants: Vec<Fr>,
+}
+
+impl<Fr: PrimeField> Ccs<Fr> {
+    pub fn from_r1cs(r1cs: &R1CS<Fr>) -> Self {
+        let mut a = Vec::with_capacity(r1cs.constraints.len());
+        let mut b = Vec::with_capacity(r1cs.constraints.len());
+        let mut c = Vec::with_capacity(r1cs.constraints.len());
+        for (a_i, b_i, c_i) in &r1cs.constraints {
+            a.push(a_i.clone());
+            b.push(b_i.clone());
+            c.push(c_i.clone());
+        }
+
+        Ccs {
+            m: r1cs.constraints.len(),
+            n: r1cs.num_variables,
+            l: r1cs.num_inputs - 1,
+            matrices: vec![a, b, c],
+            multisets: vec![vec![0, 1], vec![2]],
+            constants: vec![Fr::ONE, -Fr::ONE],
+        }
+    }
+
+    pub fn t(&self) -> usize {
+        self.matrices.len()
+    }
+
+    pub fn q(&self) -> usize {
+        self.multisets.len()
+    }
+
+    /// The highest degree of a term, 2 for R1CS.
+    pub fn degree(&self) -> usize {
+        self.multisets.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Ok, or the index of the first row of the sum that is not zero.
+    pub fn is_satisfied(&self, z: &[Fr]) -> Result<(), usize> {
+        assert_eq!(z.len(), self.n, "z needs a value for every variable");
+        assert_eq!(self.multisets.len(), self.constants.len());
+
+        let products: Vec<Vec<Fr>> = self
+            .matrices
+            .iter()
+            .map(|matrix| multiply(matrix, z))
+            .collect();
+
+        let row_sum = |row: usize| -> Fr {
+            self.multisets
+                .iter()
+                .zip(&self.constants)
+                .map(|(multiset, c)| multiset.iter().fold(*c, |acc, &i| acc * products[i][row]))
+                .sum()
+        };
+
+        match (0..self.m).find(|&row| !bool::from(row_sum(row).is_zero())) {
+            Some(row) => Err(row),
+            None => Ok(()),
+        }
+    }
+}
+
+/// M z, one entry per row.
+fn multiply<Fr: PrimeField>(matrix: &SparseMatrix<Fr>, z: &[Fr]) -> Vec<Fr> {
+    matrix
+        .iter()
+        .map(|row| row.iter().map(|(i, value)| z[*i] * value).sum())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{linear::LinearStep, poseidon_native::Fr};
+
+    #[test]
+    fn r1cs_shape() {
+        let ccs = Ccs::from_r1cs(&LinearStep::<Fr>::three_inputs().r1cs());
+
+        assert_eq!((ccs.m, ccs.n, ccs.l), (3, 8, 6));
+        assert_eq!((ccs.t(), ccs.q(), ccs.degree()), (3, 2, 2));
+    }
+
+    #[test]
+    fn linear_step_witness_satisfies_its_ccs() {
+        let step = LinearStep::<Fr>::three_inputs();
+        let ccs = Ccs::from_r1cs(&step.r1cs());
+        let mut z = step.witness(
+            &[Fr::from(4), Fr::from(7), Fr::from(8)],
+            &HashMap::from([("adder".to_string(), json!(9))]),
+        );
+
+        assert_eq!(ccs.is_satisfied(&z), Ok(()));
+
+        // step_out[1]
+        z[2] += Fr::ONE;
+        assert_eq!(ccs.is_satisfied(&z), Err(1));
+    }
+}
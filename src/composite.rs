@@ -0,0 +1,190 @@
+use std::{collections::HashMap, fmt, fs, time::Instant};
+
+use bellperson::{gadgets::num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use nova_scotia::circom::circuit::{CircomCircuit, R1CS};
+use nova_snark::traits::circuit::StepCircuit;
+use serde_json::Value;
+
+use crate::{
+    pipeline::{create_work_dir, StepCircuits},
+    witness::{compute_witness, WitnessGenerator},
+};
+
+/*
+Two step circuits chained inside one step, the output of the first is the input of the second:
+
+    step_out = second(first(step_in))
+
+Both have to have the same arity, which `Composite::new` checks. Any StepCircuit works, circom
+ones included, so for example 2inputs followed by poseidon_hash is "accumulate then hash" without
+writing a new circom circuit.
+
+The private inputs of a composite step are a single object with every name prefixed by the
+component it belongs to, e.g. { "first.adder": 3, "second.priv_hash_input": 9 }.
+*/
+#[derive(Clone)]
+pub struct Composite<A, B> {
+    pub first: A,
+    pub second: B,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompositeError {
+    /// The circuits have different arities, the output of the first cannot feed the second.
+    ArityMismatch { first: usize, second: usize },
+    /// The components have a different number of steps.
+    StepCountMismatch { first: usize, second: usize },
+}
+
+impl fmt::Display for CompositeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompositeError::ArityMismatch { first, second } => write!(
+                f,
+                "composed circuits must have the same arity, the first has {} and the second {}",
+                first, second
+            ),
+            CompositeError::StepCountMismatch { first, second } => write!(
+                f,
+                "composed components must have the same number of steps, the first has {} and \
+                 the second {}",
+                first, second
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CompositeError {}
+
+/// Ok if circuits of arities `first` and `second` can be chained.
+fn check_arities(first: usize, second: usize) -> Result<(), CompositeError> {
+    if first != second {
+        return Err(CompositeError::ArityMismatch { first, second });
+    }
+
+    Ok(())
+}
+
+impl<A, B> Composite<A, B> {
+    pub fn new<Fr: PrimeField>(first: A, second: B) -> Result<Self, CompositeError>
+    where
+        A: StepCircuit<Fr>,
+        B: StepCircuit<Fr>,
+    {
+        check_arities(first.arity(), second.arity())?;
+
+        Ok(Composite { first, second })
+    }
+}
+
+impl<Fr: PrimeField, A: StepCircuit<Fr>, B: StepCircuit<Fr>> StepCircuit<Fr> for Composite<A, B> {
+    fn arity(&self) -> usize {
+        self.first.arity()
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let z = self.first.synthesize(&mut cs.namespace(|| "first"), z)?;
+
+        self.second.synthesize(&mut cs.namespace(|| "second"), &z)
+    }
+}
+
+/// The private inputs of `component`, with the `component.` prefix removed.
+pub fn component_private_input(
+    private_input: &HashMap<String, Value>,
+    component: &str,
+) -> HashMap<String, Value> {
+    let prefix = format!("{}.", component);
+
+    private_input
+        .iter()
+        .filter_map(|(name, value)| {
+            let name = name.strip_prefix(&prefix)?;
+            Some((name.to_string(), value.clone()))
+        })
+        .collect()
+}
+
+/// Pairs up the circuits of two components step by step. The witness time of a step is the sum of
+/// both.
+pub fn compose<Fr: PrimeField, A: StepCircuit<Fr>, B: StepCircuit<Fr>>(
+    first: StepCircuits<A>,
+    second: StepCircuits<B>,
+) -> Result<StepCircuits<Composite<A, B>>, CompositeError> {
+    if first.circuits.len() != second.circuits.len() {
+        return Err(CompositeError::StepCountMismatch {
+            first: first.circuits.len(),
+            second: second.circuits.len(),
+        });
+    }
+
+    Ok(StepCircuits {
+        circuits: first
+            .circuits
+            .into_iter()
+            .zip(second.circuits)
+            .map(|(first, second)| Composite::new(first, second))
+            .collect::<Result<_, _>>()?,
+        witness_times: first
+            .witness_times
+            .iter()
+            .zip(&second.witness_times)
+            .map(|(first, second)| *first + *second)
+            .collect(),
+    })
+}
+
+/// Generates the witnesses of two circom circuits chained in every step. The second one needs the
+/// step_out of the first, so it runs after it, and the next step after both. The arities are
+/// checked before any witness is generated.
+pub fn generate_composite_circuits_sequential<Fr: PrimeField>(
+    first: (&WitnessGenerator, &R1CS<Fr>),
+    second: (&WitnessGenerator, &R1CS<Fr>),
+    private_inputs: &[HashMap<String, Value>],
+    start_public_input: &[Fr],
+) -> Result<StepCircuits<Composite<CircomCircuit<Fr>, CircomCircuit<Fr>>>, CompositeError> {
+    // The arity of a circom circuit, as CircomCircuit::arity computes it.
+    let arity = |r1cs: &R1CS<Fr>| (r1cs.num_inputs - 1) / 2;
+    check_arities(arity(first.1), arity(second.1))?;
+
+    let work_dir = create_work_dir();
+    let mut step_in = start_public_input.to_vec();
+    let mut circuits = Vec::with_capacity(private_inputs.len());
+    let mut witness_times = Vec::with_capacity(private_inputs.len());
+
+    for (i, private_input) in private_inputs.iter().enumerate() {
+        let start = Instant::now();
+        let mut components = Vec::with_capacity(2);
+        for (name, (witness_generator, r1cs)) in [("first", first), ("second", second)] {
+            let witness = compute_witness(
+                witness_generator,
+                &step_in,
+                &component_private_input(private_input, name),
+                &work_dir,
+                &format!("step_{}_{}", i, name),
+            );
+            let circuit = CircomCircuit {
+                r1cs: r1cs.clone(),
+                witness: Some(witness),
+            };
+            step_in = circuit.get_public_outputs();
+            components.push(circuit);
+        }
+        witness_times.push(start.elapsed());
+
+        let second = components.pop().unwrap();
+        let first = components.pop().unwrap();
+        circuits.push(Composite { first, second });
+    }
+
+    fs::remove_dir_all(work_dir).unwrap();
+    Ok(StepCircuits {
+        circuits,
+        witness_times,
+    })
+}
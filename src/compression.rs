@@ -0,0 +1,183 @@
+use nova_scotia::{F, S};
+use nova_snark::{
+    errors::NovaError,
+    provider::ipa_pc::EvaluationEngine,
+    spartan::ppsnark,
+    traits::{circuit::StepCircuit, Group},
+    CompressedSNARK, ProverKey, PublicParams, RecursiveSNARK, VerifierKey,
+};
+
+use crate::report::serialized_size;
+
+/// Preprocessing Spartan with IPA-PC. Proving is slower than `S`, but the verifier does not have
+/// to evaluate the R1CS matrices itself, which makes verification much faster.
+pub type SPP<G> = ppsnark::RelaxedR1CSSNARK<G, EvaluationEngine<G>>;
+
+/*
+The SNARK used to compress a RecursiveSNARK.
+
+Spartan:              S<G1>/S<G2>, what nova-scotia uses.
+PreprocessingSpartan: SPP<G1>/SPP<G2>.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressionBackend {
+    Spartan,
+    PreprocessingSpartan,
+}
+
+impl CompressionBackend {
+    pub const ALL: [CompressionBackend; 2] = [
+        CompressionBackend::Spartan,
+        CompressionBackend::PreprocessingSpartan,
+    ];
+}
+
+pub enum CompressionKeys<G1, G2, C1, C2>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    C1: StepCircuit<G1::Scalar>,
+    C2: StepCircuit<G2::Scalar>,
+{
+    Spartan(
+        ProverKey<G1, G2, C1, C2, S<G1>, S<G2>>,
+        VerifierKey<G1, G2, C1, C2, S<G1>, S<G2>>,
+    ),
+    PreprocessingSpartan(
+        ProverKey<G1, G2, C1, C2, SPP<G1>, SPP<G2>>,
+        VerifierKey<G1, G2, C1, C2, SPP<G1>, SPP<G2>>,
+    ),
+}
+
+pub enum CompressedProof<G1, G2, C1, C2>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    C1: StepCircuit<G1::Scalar>,
+    C2: StepCircuit<G2::Scalar>,
+{
+    Spartan(CompressedSNARK<G1, G2, C1, C2, S<G1>, S<G2>>),
+    PreprocessingSpartan(CompressedSNARK<G1, G2, C1, C2, SPP<G1>, SPP<G2>>),
+}
+
+impl<G1, G2, C1, C2> CompressionKeys<G1, G2, C1, C2>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    C1: StepCircuit<G1::Scalar>,
+    C2: StepCircuit<G2::Scalar>,
+{
+    pub fn setup(
+        backend: CompressionBackend,
+        pp: &PublicParams<G1, G2, C1, C2>,
+    ) -> Result<Self, NovaError> {
+        Ok(match backend {
+            CompressionBackend::Spartan => {
+                let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(pp)?;
+                CompressionKeys::Spartan(pk, vk)
+            }
+            CompressionBackend::PreprocessingSpartan => {
+                let (pk, vk) = CompressedSNARK::<_, _, _, _, SPP<G1>, SPP<G2>>::setup(pp)?;
+                CompressionKeys::PreprocessingSpartan(pk, vk)
+            }
+        })
+    }
+
+    pub fn backend(&self) -> CompressionBackend {
+        match self {
+            CompressionKeys::Spartan(..) => CompressionBackend::Spartan,
+            CompressionKeys::PreprocessingSpartan(..) => CompressionBackend::PreprocessingSpartan,
+        }
+    }
+
+    pub fn prover_key_size(&self) -> usize {
+        match self {
+            CompressionKeys::Spartan(pk, _) => serialized_size(pk),
+            CompressionKeys::PreprocessingSpartan(pk, _) => serialized_size(pk),
+        }
+    }
+
+    pub fn verifier_key_size(&self) -> usize {
+        match self {
+            CompressionKeys::Spartan(_, vk) => serialized_size(vk),
+            CompressionKeys::PreprocessingSpartan(_, vk) => serialized_size(vk),
+        }
+    }
+
+    pub fn prove(
+        &self,
+        pp: &PublicParams<G1, G2, C1, C2>,
+        recursive_snark: &RecursiveSNARK<G1, G2, C1, C2>,
+    ) -> Result<CompressedProof<G1, G2, C1, C2>, NovaError> {
+        Ok(match self {
+            CompressionKeys::Spartan(pk, _) => {
+                CompressedProof::Spartan(CompressedSNARK::prove(pp, pk, recursive_snark)?)
+            }
+            CompressionKeys::PreprocessingSpartan(pk, _) => CompressedProof::PreprocessingSpartan(
+                CompressedSNARK::prove(pp, pk, recursive_snark)?,
+            ),
+        })
+    }
+}
+
+impl<G1, G2, C1, C2> CompressedProof<G1, G2, C1, C2>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    C1: StepCircuit<G1::Scalar>,
+    C2: StepCircuit<G2::Scalar>,
+{
+    /// Fails with `ProofVerifyError` if `keys` were set up for the other backend.
+    pub fn verify(
+        &self,
+        keys: &CompressionKeys<G1, G2, C1, C2>,
+        num_steps: usize,
+        z0_primary: Vec<F<G1>>,
+        z0_secondary: Vec<F<G2>>,
+    ) -> Result<(Vec<F<G1>>, Vec<F<G2>>), NovaError> {
+        match (self, keys) {
+            (CompressedProof::Spartan(snark), CompressionKeys::Spartan(_, vk)) => {
+                snark.verify(vk, num_steps, z0_primary, z0_secondary)
+            }
+            (
+                CompressedProof::PreprocessingSpartan(snark),
+                CompressionKeys::PreprocessingSpartan(_, vk),
+            ) => snark.verify(vk, num_steps, z0_primary, z0_secondary),
+            _ => Err(NovaError::ProofVerifyError),
+        }
+    }
+
+    pub fn backend(&self) -> CompressionBackend {
+        match self {
+            CompressedProof::Spartan(_) => CompressionBackend::Spartan,
+            CompressedProof::PreprocessingSpartan(_) => CompressionBackend::PreprocessingSpartan,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            CompressedProof::Spartan(snark) => bincode::serialize(snark),
+            CompressedProof::PreprocessingSpartan(snark) => bincode::serialize(snark),
+        }
+        .unwrap()
+    }
+
+    /// Reads back a proof written by `to_bytes`. The encoding does not record the backend, so the
+    /// caller has to know it.
+    pub fn from_bytes(backend: CompressionBackend, bytes: &[u8]) -> Result<Self, bincode::Error> {
+        Ok(match backend {
+            CompressionBackend::Spartan => CompressedProof::Spartan(bincode::deserialize(bytes)?),
+            CompressionBackend::PreprocessingSpartan => {
+                CompressedProof::PreprocessingSpartan(bincode::deserialize(bytes)?)
+            }
+        })
+    }
+
+    /// Size of the proof once serialized with bincode.
+    pub fn serialized_size(&self) -> usize {
+        match self {
+            CompressedProof::Spartan(snark) => serialized_size(snark),
+            CompressedProof::PreprocessingSpartan(snark) => serialized_size(snark),
+        }
+    }
+}
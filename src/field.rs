@@ -0,0 +1,193 @@
+use std::fmt;
+
+use ff::PrimeField;
+use num_bigint::{BigInt, BigUint, Sign};
+use serde_json::Value;
+
+/*
+Conversions between field elements and the formats they take outside of the crate: circom input
+JSON, decimal and hex strings and BigUint. Every function works for F<G1> and F<G2> of both curve
+cycles, whose to_repr is little endian.
+
+Integers outside of [0, p) are reduced into the field, so -1 becomes p - 1 and values larger than a
+u64 are taken mod p, the same as circom does with its inputs.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFieldError {
+    /// The string is not a (possibly negative) decimal or 0x-prefixed hex integer.
+    InvalidInteger(String),
+    /// The JSON value is not an integer or a string holding one.
+    InvalidJson(Value),
+}
+
+impl fmt::Display for ParseFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseFieldError::InvalidInteger(s) => write!(f, "{:?} is not an integer", s),
+            ParseFieldError::InvalidJson(value) => {
+                write!(f, "{} is not an integer or an integer string", value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseFieldError {}
+
+pub fn modulus<Fr: PrimeField>() -> BigUint {
+    BigUint::parse_bytes(Fr::MODULUS.trim_start_matches("0x").as_bytes(), 16).unwrap()
+}
+
+pub fn to_biguint<Fr: PrimeField>(x: &Fr) -> BigUint {
+    BigUint::from_bytes_le(x.to_repr().as_ref())
+}
+
+pub fn from_biguint<Fr: PrimeField>(x: &BigUint) -> Fr {
+    let mut repr = Fr::Repr::default();
+    let bytes = (x % modulus::<Fr>()).to_bytes_le();
+    repr.as_mut()[..bytes.len()].copy_from_slice(&bytes);
+
+    Fr::from_repr(repr).unwrap()
+}
+
+pub fn from_bigint<Fr: PrimeField>(x: &BigInt) -> Fr {
+    let (sign, magnitude) = x.clone().into_parts();
+    let x = from_biguint::<Fr>(&magnitude);
+
+    match sign {
+        Sign::Minus => -x,
+        _ => x,
+    }
+}
+
+pub fn to_decimal_string<Fr: PrimeField>(x: &Fr) -> String {
+    to_biguint(x).to_str_radix(10)
+}
+
+/// `[x_0, x_1, ...]` in decimal, for printing states.
+pub fn to_decimal_list<Fr: PrimeField>(xs: &[Fr]) -> String {
+    let xs: Vec<String> = xs.iter().map(to_decimal_string).collect();
+
+    format!("[{}]", xs.join(", "))
+}
+
+/// Big endian, 0x-prefixed and padded to the size of the field, the format nova prints.
+pub fn to_hex<Fr: PrimeField>(x: &Fr) -> String {
+    let hex: String = x
+        .to_repr()
+        .as_ref()
+        .iter()
+        .rev()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    format!("0x{}", hex)
+}
+
+/// Parses a decimal or a 0x/0X-prefixed hex integer, either of them optionally negative. Digits
+/// only, BigUint's `_` separators are not accepted.
+pub fn from_str<Fr: PrimeField>(s: &str) -> Result<Fr, ParseFieldError> {
+    let invalid = || ParseFieldError::InvalidInteger(s.to_string());
+
+    let (sign, digits) = match s.trim().strip_prefix('-') {
+        Some(digits) => (Sign::Minus, digits),
+        None => (Sign::Plus, s.trim()),
+    };
+    let (digits, radix) = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => (hex, 16),
+        None => (digits, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(invalid());
+    }
+    let magnitude = BigUint::parse_bytes(digits.as_bytes(), radix).ok_or_else(invalid)?;
+
+    Ok(from_bigint(&BigInt::from_biguint(sign, magnitude)))
+}
+
+/// Circom accepts inputs as decimal strings, which also covers values that do not fit in a JSON
+/// number.
+pub fn to_json<Fr: PrimeField>(x: &Fr) -> Value {
+    Value::String(to_decimal_string(x))
+}
+
+/// Reads a JSON integer (negative ones included) or a string accepted by [`from_str`].
+pub fn from_json<Fr: PrimeField>(value: &Value) -> Result<Fr, ParseFieldError> {
+    match value {
+        Value::String(s) => from_str(s),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(x), _) => Ok(Fr::from(x)),
+            (None, Some(x)) => Ok(-Fr::from(x.unsigned_abs())),
+            _ => Err(ParseFieldError::InvalidJson(value.clone())),
+        },
+        _ => Err(ParseFieldError::InvalidJson(value.clone())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nova_scotia::F;
+    use nova_snark::provider;
+    use serde_json::json;
+
+    type Fr = F<provider::bn256_grumpkin::bn256::Point>;
+    type Fq = F<provider::bn256_grumpkin::grumpkin::Point>;
+
+    #[test]
+    fn negative_values_wrap_around_the_modulus() {
+        assert_eq!(from_json::<Fr>(&json!(-1)).unwrap(), -Fr::from(1));
+        assert_eq!(from_str::<Fr>("-5").unwrap(), -Fr::from(5));
+        assert_eq!(to_biguint(&-Fr::from(1)), modulus::<Fr>() - 1u32);
+    }
+
+    #[test]
+    fn values_larger_than_u64() {
+        let x = BigUint::from(u64::MAX) * 1000u32 + 7u32;
+        let fr: Fr = from_str(&x.to_str_radix(10)).unwrap();
+
+        assert_eq!(to_biguint(&fr), x);
+        assert_eq!(from_str::<Fr>(&format!("0x{}", x.to_str_radix(16))), Ok(fr));
+        assert_eq!(from_str::<Fr>(&to_hex(&fr)), Ok(fr));
+        assert_eq!(from_json::<Fr>(&to_json(&fr)), Ok(fr));
+        // The modulus itself is reduced to zero.
+        assert_eq!(from_biguint::<Fr>(&(modulus::<Fr>() + &x)), fr);
+    }
+
+    #[test]
+    fn round_trips_on_both_fields() {
+        let fr = -Fr::from(123456789);
+        let fq = -Fq::from(123456789);
+
+        assert_eq!(from_str::<Fr>(&to_decimal_string(&fr)), Ok(fr));
+        assert_eq!(from_str::<Fq>(&to_decimal_string(&fq)), Ok(fq));
+        assert_eq!(from_str::<Fq>(&to_hex(&fq)), Ok(fq));
+        assert_ne!(to_decimal_string(&fr), to_decimal_string(&fq));
+    }
+
+    #[test]
+    fn decimal_list() {
+        assert_eq!(to_decimal_list(&[Fr::from(25), Fr::from(0)]), "[25, 0]");
+        assert_eq!(to_decimal_list::<Fr>(&[]), "[]");
+    }
+
+    #[test]
+    fn uppercase_hex_prefix() {
+        assert_eq!(from_str::<Fr>("0XfF"), Ok(Fr::from(255)));
+        assert_eq!(from_str::<Fr>("-0X10"), Ok(-Fr::from(16)));
+    }
+
+    #[test]
+    fn rejects_non_integers() {
+        assert!(from_str::<Fr>("0x").is_err());
+        assert!(from_str::<Fr>("12a").is_err());
+        assert!(from_str::<Fr>("1_000").is_err());
+        assert!(from_str::<Fr>("0xff_ff").is_err());
+        assert!(from_str::<Fr>("_1").is_err());
+        assert!(from_json::<Fr>(&json!(1.5)).is_err());
+        assert!(from_json::<Fr>(&json!([1])).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::field::{self, to_hex};
+
+/*
+Golden outputs of a circuit: the inputs of a run and the z_n it is expected to produce, stored next
+to the .r1cs as <circuit>.golden.json. Field elements are big endian hex, the same format
+RecursiveSNARK::verify prints.
+
+When a circuit is changed on purpose, regenerate the files with
+
+    UPDATE_GOLDEN=1 cargo test --release poseidon
+
+and review the diff before committing it.
+*/
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Golden {
+    pub start_public_input: Vec<String>,
+    /// Sorted by name so regenerated files are stable.
+    pub private_inputs: Vec<BTreeMap<String, Value>>,
+    pub iteration_count: usize,
+    pub z_last: Vec<String>,
+}
+
+impl Golden {
+    pub fn new<Fr: PrimeField>(
+        start_public_input: &[Fr],
+        private_inputs: &[HashMap<String, Value>],
+        z_last: &[Fr],
+    ) -> Self {
+        Golden {
+            start_public_input: start_public_input.iter().map(to_hex).collect(),
+            private_inputs: private_inputs
+                .iter()
+                .map(|input| input.clone().into_iter().collect())
+                .collect(),
+            iteration_count: private_inputs.len(),
+            z_last: z_last.iter().map(to_hex).collect(),
+        }
+    }
+
+    /// The start_public_input and private inputs of the run, to replay it.
+    pub fn inputs<Fr: PrimeField>(&self) -> (Vec<Fr>, Vec<HashMap<String, Value>>) {
+        let start_public_input = self
+            .start_public_input
+            .iter()
+            .map(|x| field::from_str(x).unwrap())
+            .collect();
+        let private_inputs = self
+            .private_inputs
+            .iter()
+            .map(|input| input.clone().into_iter().collect())
+            .collect();
+
+        (start_public_input, private_inputs)
+    }
+
+    /// `circuits/poseidon/poseidon_hash.r1cs` -> `circuits/poseidon/poseidon_hash.golden.json`
+    pub fn path_for(r1cs: &Path) -> PathBuf {
+        r1cs.with_extension("golden.json")
+    }
+
+    pub fn load(path: &Path) -> Self {
+        let file = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("could not read {}: {}", path.display(), e));
+
+        serde_json::from_str(&file).unwrap()
+    }
+
+    pub fn write(&self, path: &Path) {
+        let mut json = serde_json::to_string_pretty(self).unwrap();
+        json.push('\n');
+        fs::write(path, json).unwrap();
+    }
+}
+
+/// Compares a run against the golden file at `path`, or rewrites the file when `UPDATE_GOLDEN` is
+/// set.
+pub fn check<Fr: PrimeField>(
+    path: &Path,
+    start_public_input: &[Fr],
+    private_inputs: &[HashMap<String, Value>],
+    z_last: &[Fr],
+) {
+    let actual = Golden::new(start_public_input, private_inputs, z_last);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        println!("Updating {}", path.display());
+        actual.write(path);
+        return;
+    }
+
+    assert_eq!(
+        Golden::load(path),
+        actual,
+        "{} is out of date, rerun with UPDATE_GOLDEN=1 if the circuit changed on purpose",
+        path.display()
+    );
+}
@@ -0,0 +1,410 @@
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use ff::PrimeField;
+use serde_json::Value;
+
+use crate::field::{self, ParseFieldError};
+
+/*
+Private inputs of a step are a JSON object from signal name to value, exactly what circom expects
+in input.json. A value is a field element (a JSON integer, or a decimal or 0x-prefixed hex string)
+or an array of values, nested once per dimension of the signal:
+
+    signal input leaf;              ->  "leaf": 9
+    signal input path[3];           ->  "path": [1, "2", "0x03"]
+    signal input matrix[2][2];      ->  "matrix": [[1, 2], [3, 4]]
+
+The declared shape of every input comes from the .sym file circom writes with `--sym`.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    /// The circuit has no input signal with this name.
+    UnknownSignal(String),
+    /// A signal of the circuit is missing from the private input.
+    MissingSignal(String),
+    /// The value has a different shape than the declared signal, or is a ragged array.
+    ShapeMismatch {
+        signal: String,
+        expected: Vec<usize>,
+        found: Option<Vec<usize>>,
+    },
+    /// An element is not a field element.
+    InvalidElement {
+        signal: String,
+        error: ParseFieldError,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::UnknownSignal(signal) => write!(f, "the circuit has no input {}", signal),
+            InputError::MissingSignal(signal) => write!(f, "input {} is missing", signal),
+            InputError::ShapeMismatch {
+                signal,
+                expected,
+                found: Some(found),
+            } => write!(
+                f,
+                "input {} has shape {:?}, expected {:?}",
+                signal, found, expected
+            ),
+            InputError::ShapeMismatch {
+                signal, expected, ..
+            } => write!(
+                f,
+                "input {} is a ragged array, expected shape {:?}",
+                signal, expected
+            ),
+            InputError::InvalidElement { signal, error } => {
+                write!(f, "input {}: {}", signal, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/*
+The header section of a .r1cs file counts the signals in front of the witness, which circom lays out
+as
+
+    [1, outputs, public inputs, private inputs, intermediate signals...]
+
+so the private inputs of the main component are the signals with a label in
+[1 + outputs + public_inputs, 1 + outputs + public_inputs + private_inputs). step_in is always
+public, it is not among them.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalCounts {
+    pub outputs: usize,
+    pub public_inputs: usize,
+    pub private_inputs: usize,
+}
+
+impl SignalCounts {
+    pub fn load(r1cs_path: impl AsRef<Path>) -> Self {
+        let r1cs = fs::read(r1cs_path).unwrap();
+
+        Self::parse(&r1cs)
+    }
+
+    /// Reads the header section: `"r1cs"`, version and section count, then sections of
+    /// `(type: u32, size: u64, content)`. The header is type 1, starting with the field size in
+    /// bytes and the prime, followed by the number of wires, public outputs, public inputs and
+    /// private inputs, all little endian.
+    pub fn parse(r1cs: &[u8]) -> Self {
+        assert_eq!(&r1cs[..4], b"r1cs", "not a .r1cs file");
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes(r1cs[offset..offset + 4].try_into().unwrap()) as usize
+        };
+
+        let mut offset = 12;
+        loop {
+            let section_type = u32_at(offset);
+            let size = u64::from_le_bytes(r1cs[offset + 4..offset + 12].try_into().unwrap());
+            offset += 12;
+            if section_type == 1 {
+                break;
+            }
+            offset += size as usize;
+        }
+
+        // Skips the field size, the prime and the number of wires.
+        let counts = offset + 4 + u32_at(offset) + 4;
+        SignalCounts {
+            outputs: u32_at(counts),
+            public_inputs: u32_at(counts + 4),
+            private_inputs: u32_at(counts + 8),
+        }
+    }
+
+    fn is_private_input(&self, label: usize) -> bool {
+        let first = 1 + self.outputs + self.public_inputs;
+
+        (first..first + self.private_inputs).contains(&label)
+    }
+}
+
+/// Declared shapes of the private input signals of the main component, a scalar has shape `[]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignalShapes(pub HashMap<String, Vec<usize>>);
+
+impl SignalShapes {
+    pub fn load(sym_path: impl AsRef<Path>, r1cs_path: impl AsRef<Path>) -> Self {
+        let sym = fs::read_to_string(sym_path).unwrap();
+
+        Self::parse(&sym, &SignalCounts::load(r1cs_path))
+    }
+
+    /*
+    Every line of a .sym file is `label_id,witness_id,component_id,name`, with one line per scalar
+    signal of every component, e.g. `7,7,0,main.path[2]`. Only the private inputs are kept, the
+    outputs, step_in, intermediate signals and signals of subcomponents (`main.hash.inputs[0]`) are
+    skipped. The shape is one past the largest index seen in every dimension.
+    */
+    pub fn parse(sym: &str, counts: &SignalCounts) -> Self {
+        let mut shapes: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for line in sym.lines().filter(|line| !line.trim().is_empty()) {
+            let columns: Vec<&str> = line.splitn(4, ',').collect();
+            if !counts.is_private_input(columns[0].trim().parse().unwrap()) {
+                continue;
+            }
+            let Some(name) = columns[3].trim().strip_prefix("main.") else {
+                continue;
+            };
+            if name.contains('.') {
+                continue;
+            }
+
+            let (base, indices) = match name.find('[') {
+                Some(i) => (&name[..i], &name[i..]),
+                None => (name, ""),
+            };
+            let indices: Vec<usize> = indices
+                .split(|c| c == '[' || c == ']')
+                .filter(|index| !index.is_empty())
+                .map(|index| index.parse().unwrap())
+                .collect();
+
+            let shape = shapes
+                .entry(base.to_string())
+                .or_insert_with(|| vec![0; indices.len()]);
+            for (dim, index) in shape.iter_mut().zip(indices) {
+                *dim = (*dim).max(index + 1);
+            }
+        }
+
+        SignalShapes(shapes)
+    }
+
+    /// Checks that every signal in `private_input` is a private input of the circuit with the same
+    /// shape. `step_in` is filled in by the pipeline, it and the outputs may not appear in it.
+    pub fn validate<Fr: PrimeField>(
+        &self,
+        private_input: &HashMap<String, Value>,
+    ) -> Result<(), InputError> {
+        for (signal, value) in private_input {
+            let expected = self
+                .0
+                .get(signal)
+                .ok_or_else(|| InputError::UnknownSignal(signal.clone()))?;
+
+            let found = shape_of(value);
+            if found.as_ref() != Some(expected) {
+                return Err(InputError::ShapeMismatch {
+                    signal: signal.clone(),
+                    expected: expected.clone(),
+                    found,
+                });
+            }
+
+            flatten::<Fr>(value).map_err(|error| InputError::InvalidElement {
+                signal: signal.clone(),
+                error,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Like [`SignalShapes::validate`], also requiring every signal in `signals` to be present.
+    pub fn validate_complete<Fr: PrimeField>(
+        &self,
+        private_input: &HashMap<String, Value>,
+        signals: &[&str],
+    ) -> Result<(), InputError> {
+        if let Some(missing) = signals.iter().find(|s| !private_input.contains_key(**s)) {
+            return Err(InputError::MissingSignal(missing.to_string()));
+        }
+
+        self.validate::<Fr>(private_input)
+    }
+}
+
+/// The shape of a JSON value, or `None` for a ragged array.
+pub fn shape_of(value: &Value) -> Option<Vec<usize>> {
+    match value {
+        Value::Array(elements) => {
+            let inner = match elements.first() {
+                Some(first) => shape_of(first)?,
+                None => Vec::new(),
+            };
+            for element in elements {
+                if shape_of(element)? != inner {
+                    return None;
+                }
+            }
+
+            let mut shape = vec![elements.len()];
+            shape.extend(inner);
+            Some(shape)
+        }
+        _ => Some(Vec::new()),
+    }
+}
+
+/// Reads every element of a (possibly nested) value in row major order, the order circom assigns
+/// them to signals.
+pub fn flatten<Fr: PrimeField>(value: &Value) -> Result<Vec<Fr>, ParseFieldError> {
+    match value {
+        Value::Array(elements) => {
+            let mut flat = Vec::new();
+            for element in elements {
+                flat.extend(flatten::<Fr>(element)?);
+            }
+
+            Ok(flat)
+        }
+        value => Ok(vec![field::from_json(value)?]),
+    }
+}
+
+/// Rewrites every element as a decimal string in the field, keeping the nesting. circom's
+/// generators do not understand hex or negative numbers.
+pub fn to_circom_input<Fr: PrimeField>(value: &Value) -> Result<Value, ParseFieldError> {
+    match value {
+        Value::Array(elements) => elements
+            .iter()
+            .map(to_circom_input::<Fr>)
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        value => Ok(field::to_json(&field::from_json::<Fr>(value)?)),
+    }
+}
+
+/// Loads the private inputs of every step from a JSON array of objects.
+pub fn load_private_inputs(path: impl AsRef<Path>) -> Vec<HashMap<String, Value>> {
+    let file = fs::read_to_string(path).unwrap();
+
+    serde_json::from_str(&file).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nova_scotia::F;
+    use nova_snark::provider;
+    use serde_json::json;
+
+    type Fr = F<provider::bn256_grumpkin::bn256::Point>;
+
+    // What circom 2.1 writes for circuits/array_inputs/array_inputs.circom, plus an intermediate
+    // signal and a subcomponent.
+    const SYM: &str = "1,1,0,main.step_out[0]
+2,2,0,main.step_out[1]
+3,3,0,main.step_in[0]
+4,4,0,main.step_in[1]
+5,5,0,main.adders[0]
+6,6,0,main.adders[1]
+7,7,0,main.adders[2]
+8,8,0,main.matrix[0][0]
+9,9,0,main.matrix[0][1]
+10,10,0,main.matrix[1][0]
+11,11,0,main.matrix[1][1]
+12,12,0,main.sum
+13,-1,1,main.hash.inputs[0]
+";
+    const COUNTS: SignalCounts = SignalCounts {
+        outputs: 2,
+        public_inputs: 2,
+        private_inputs: 7,
+    };
+
+    fn input(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn parses_shapes_from_sym() {
+        let shapes = SignalShapes::parse(SYM, &COUNTS);
+
+        assert_eq!(shapes.0.len(), 2);
+        assert_eq!(shapes.0["adders"], vec![3]);
+        assert_eq!(shapes.0["matrix"], vec![2, 2]);
+        let counts = SignalCounts {
+            outputs: 1,
+            public_inputs: 0,
+            private_inputs: 1,
+        };
+        assert_eq!(
+            SignalShapes::parse("1,1,0,main.step_out\n2,2,0,main.leaf\n", &counts).0["leaf"],
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn validates_nested_inputs() {
+        let shapes = SignalShapes::parse(SYM, &COUNTS);
+        let valid = input(json!({ "adders": [1, "2", "0x03"], "matrix": [[1, 2], [-3, 4]] }));
+
+        assert_eq!(
+            shapes.validate_complete::<Fr>(&valid, &["adders", "matrix"]),
+            Ok(())
+        );
+        assert_eq!(
+            shapes.validate::<Fr>(&input(json!({ "matrix": [[1, 2], [3]] }))),
+            Err(InputError::ShapeMismatch {
+                signal: "matrix".to_string(),
+                expected: vec![2, 2],
+                found: None,
+            })
+        );
+        assert_eq!(
+            shapes.validate::<Fr>(&input(json!({ "adders": [1, 2] }))),
+            Err(InputError::ShapeMismatch {
+                signal: "adders".to_string(),
+                expected: vec![3],
+                found: Some(vec![2]),
+            })
+        );
+        assert_eq!(
+            shapes.validate::<Fr>(&input(json!({ "step_in": [1, 2] }))),
+            Err(InputError::UnknownSignal("step_in".to_string()))
+        );
+        assert_eq!(
+            shapes.validate::<Fr>(&input(json!({ "step_out": [1, 2] }))),
+            Err(InputError::UnknownSignal("step_out".to_string()))
+        );
+        assert_eq!(
+            shapes.validate::<Fr>(&input(json!({ "sum": 1 }))),
+            Err(InputError::UnknownSignal("sum".to_string()))
+        );
+        assert_eq!(
+            shapes.validate_complete::<Fr>(&input(json!({ "adders": [1, 2, 3] })), &["matrix"]),
+            Err(InputError::MissingSignal("matrix".to_string()))
+        );
+        assert!(matches!(
+            shapes.validate::<Fr>(&input(json!({ "adders": [1, 2, "x"] }))),
+            Err(InputError::InvalidElement { .. })
+        ));
+    }
+
+    #[test]
+    fn reads_signal_counts_from_r1cs() {
+        assert_eq!(
+            SignalCounts::load("circuits/poseidon/poseidon_hash.r1cs"),
+            SignalCounts {
+                outputs: 2,
+                public_inputs: 2,
+                private_inputs: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn flattens_in_row_major_order() {
+        let matrix = json!([[1, 2], [-3, "0x04"]]);
+
+        assert_eq!(
+            flatten::<Fr>(&matrix).unwrap(),
+            vec![Fr::from(1), Fr::from(2), -Fr::from(3), Fr::from(4)]
+        );
+        assert_eq!(
+            to_circom_input::<Fr>(&matrix).unwrap(),
+            json!([["1", "2"], [field::to_decimal_string(&-Fr::from(3)), "4"]])
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::{fs, path::Path};
+
+use ff::PrimeField;
+use nova_scotia::{C2, F};
+use nova_snark::{
+    errors::NovaError,
+    traits::{circuit::StepCircuit, Group},
+    PublicParams, RecursiveSNARK,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    field,
+    pipeline::{fold_inspecting, StepCircuits},
+    trace::FoldingTrace,
+};
+
+/*
+The running relaxed R1CS instances of a RecursiveSNARK, read after every fold:
+
+    U = (comm_W, comm_E, u, X)
+
+where u is the scalar that absorbs the folding randomness (1 for a fresh instance, anything after
+folding) and X the public IO, which for Nova's augmented circuits is two hashes of the state. The
+commitments are points, so only a short digest of them is kept, enough to see when they change.
+
+nova-snark keeps these fields private, they are read back from the serialized RecursiveSNARK. After
+the first step r_U_primary is still the default instance (u = 0), the step itself only gets folded
+in by the next one.
+*/
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RelaxedInstance {
+    pub u: String,
+    pub x: Vec<String>,
+    pub comm_w: String,
+    pub comm_e: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FoldRecord {
+    pub step: usize,
+    pub primary: RelaxedInstance,
+    pub secondary: RelaxedInstance,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FoldingIntrospection {
+    pub steps: Vec<FoldRecord>,
+}
+
+impl FoldingIntrospection {
+    pub fn record<G1, G2, C1, C2>(&mut self, recursive_snark: &RecursiveSNARK<G1, G2, C1, C2>)
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+        C1: StepCircuit<G1::Scalar>,
+        C2: StepCircuit<G2::Scalar>,
+    {
+        let snark = serde_json::to_value(recursive_snark).unwrap();
+
+        self.steps.push(FoldRecord {
+            step: self.steps.len(),
+            primary: RelaxedInstance::read::<G1::Scalar>(&snark["r_U_primary"]),
+            secondary: RelaxedInstance::read::<G2::Scalar>(&snark["r_U_secondary"]),
+        });
+    }
+
+    /// One row per step, the primary instance first. X is joined with `;` as its length depends on
+    /// the circuit.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "step,primary_u,primary_x,primary_comm_w,primary_comm_e,\
+                       secondary_u,secondary_x,secondary_comm_w,secondary_comm_e\n"
+            .to_string();
+        for record in &self.steps {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                record.step,
+                record.primary.to_csv(),
+                record.secondary.to_csv()
+            ));
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn write_csv(&self, path: impl AsRef<Path>) {
+        fs::write(path, self.to_csv()).unwrap();
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) {
+        fs::write(path, self.to_json()).unwrap();
+    }
+}
+
+impl RelaxedInstance {
+    fn read<Fr: PrimeField + DeserializeOwned>(instance: &Value) -> Self {
+        let scalar = |value: &Value| {
+            let x: Fr = serde_json::from_value(value.clone()).unwrap();
+            field::to_decimal_string(&x)
+        };
+
+        RelaxedInstance {
+            u: scalar(&instance["u"]),
+            x: instance["X"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(scalar)
+                .collect(),
+            comm_w: digest(&instance["comm_W"]),
+            comm_e: digest(&instance["comm_E"]),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            self.u,
+            self.x.join(";"),
+            self.comm_w,
+            self.comm_e
+        )
+    }
+}
+
+/// The first 8 bytes of the sha256 of the serialized commitment, in hex.
+fn digest(commitment: &Value) -> String {
+    let hash = Sha256::digest(commitment.to_string().as_bytes());
+
+    hash[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// [`fold`](crate::pipeline::fold), recording the running instances after every step.
+pub fn fold_introspected<G1, G2, C>(
+    pp: &PublicParams<G1, G2, C, C2<G2>>,
+    step_circuits: &StepCircuits<C>,
+    start_public_input: &[F<G1>],
+    z0_secondary: &[F<G2>],
+) -> Result<
+    (
+        RecursiveSNARK<G1, G2, C, C2<G2>>,
+        FoldingTrace,
+        FoldingIntrospection,
+    ),
+    NovaError,
+>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    C: StepCircuit<F<G1>>,
+{
+    let mut introspection = FoldingIntrospection::default();
+    let (recursive_snark, trace) = fold_inspecting(
+        pp,
+        step_circuits,
+        start_public_input,
+        z0_secondary,
+        |recursive_snark| introspection.record(recursive_snark),
+    )?;
+
+    Ok((recursive_snark, trace, introspection))
+}
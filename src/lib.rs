@@ -1,26 +1,114 @@
-mod bn254_2_inputs;
-mod bn254_3_inputs;
-mod pasta_2_inputs;
-mod poseidon;
-mod poseidon_2_priv_inputs;
+pub mod ark;
+pub mod bn254_2_inputs;
+pub mod bn254_3_inputs;
+pub mod ccs;
+pub mod composite;
+pub mod compression;
+pub mod field;
+pub mod golden;
+pub mod inputs;
+pub mod introspect;
+pub mod linear;
+pub mod model;
+pub mod native_circuits;
+pub mod nonuniform;
+pub mod pasta_2_inputs;
+pub mod pipeline;
+pub mod poseidon;
+pub mod poseidon_2_priv_inputs;
+pub mod poseidon_circuit;
+pub mod poseidon_constants;
+pub mod poseidon_native;
+pub mod relaxed_r1cs;
+pub mod report;
+pub mod trace;
+pub mod trajectory;
+pub mod unroll;
+pub mod witness;
 
 #[cfg(test)]
 mod test {
+    use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+    use nova_scotia::{
+        circom::{
+            circuit::{CircomCircuit, R1CS},
+            reader::load_r1cs,
+        },
+        create_public_params, FileLocation, C1, C2, F,
+    };
+    use nova_snark::{
+        errors::NovaError,
+        provider,
+        traits::{
+            circuit::{StepCircuit, TrivialTestCircuit},
+            Group,
+        },
+        PublicParams, RecursiveSNARK,
+    };
+    use proptest::{collection::vec, prelude::*};
+    use serde_json::json;
+
     use crate::bn254_2_inputs::run_bn254_2inputs;
     use crate::bn254_3_inputs::run_bn254_3inputs;
+    use crate::ccs::Ccs;
+    use crate::composite::{
+        component_private_input, compose, generate_composite_circuits_sequential, Composite,
+        CompositeError,
+    };
+    use crate::compression::{CompressedProof, CompressionBackend, CompressionKeys};
+    use crate::field::to_json;
+    use crate::golden::Golden;
+    use crate::inputs::load_private_inputs;
+    use crate::introspect::fold_introspected;
+    use crate::linear::LinearStep;
+    use crate::model::{
+        predict_output, Affine, ArrayInputs, Composed, Poseidon2PrivInputs, PoseidonHash,
+        StepModel, ThreeInputs, TwoInputs,
+    };
+    use crate::native_circuits::{adder_step_circuits, ThreeInputsCircuit, TwoInputsCircuit};
+    use crate::nonuniform::{
+        encode_program, program_step_circuits, AddOrHash, InstructionSet, NonUniformCircuit, ADD,
+        HASH,
+    };
     use crate::pasta_2_inputs::run_pasta_2inputs;
+    use crate::pipeline::{
+        create_recursive_circuit, fold, generate_circuits, generate_circuits_sequential,
+        time_witness_generation, WitnessGeneration,
+    };
     use crate::poseidon::poseidon;
     use crate::poseidon_2_priv_inputs::poseidon_2_priv_inputs;
+    use crate::poseidon_circuit::{poseidon_step_circuits, PoseidonCircuit};
+    use crate::relaxed_r1cs::{
+        fold_witnesses, is_satisfied, CommitmentKey, RelaxedR1CSWitness, Unsatisfied,
+    };
+    use crate::trace::FoldingTrace;
+    use crate::trajectory::Trajectory;
+    use crate::unroll::{unroll, Unrolled};
+    use crate::witness::{CircuitManifest, WitnessGenerator, WitnessGeneratorKind};
+
+    type G1 = provider::bn256_grumpkin::bn256::Point;
+    type G2 = provider::bn256_grumpkin::grumpkin::Point;
 
     #[test]
     fn test_bn254_2inputs_folding() {
         let circuit_filepath = "circuits/2inputs/bn254/bn254.r1cs";
         let witness_gen_filepath = "circuits/2inputs/bn254/bn254.wasm";
 
-        run_bn254_2inputs(
-            circuit_filepath.to_string().clone(),
-            witness_gen_filepath.to_string(),
-        );
+        for backend in CompressionBackend::ALL {
+            let report = run_bn254_2inputs(
+                circuit_filepath.to_string().clone(),
+                witness_gen_filepath.to_string(),
+                vec![F::<G2>::from(0)],
+                backend,
+            );
+
+            let sizes = report.sizes;
+            assert!(sizes.public_params > 0);
+            assert!(sizes.prover_key > 0);
+            assert!(sizes.verifier_key > 0);
+            assert!(sizes.compressed_snark < sizes.recursive_snark);
+        }
     }
 
     #[test]
@@ -28,10 +116,14 @@ mod test {
         let circuit_filepath = "circuits/2inputs/pasta/pasta.r1cs";
         let witness_gen_filepath = "circuits/2inputs/pasta/pasta.wasm";
 
-        run_pasta_2inputs(
-            circuit_filepath.to_string().clone(),
-            witness_gen_filepath.to_string(),
-        );
+        for backend in CompressionBackend::ALL {
+            run_pasta_2inputs(
+                circuit_filepath.to_string().clone(),
+                witness_gen_filepath.to_string(),
+                vec![F::<pasta_curves::pallas::Point>::from(0)],
+                backend,
+            );
+        }
     }
 
     #[test]
@@ -39,9 +131,574 @@ mod test {
         let circuit_filepath = "circuits/3inputs/bn254/3inputs.r1cs";
         let witness_gen_filepath = "circuits/3inputs/bn254/3inputs.wasm";
 
-        run_bn254_3inputs(
-            circuit_filepath.to_string().clone(),
-            witness_gen_filepath.to_string(),
+        for backend in CompressionBackend::ALL {
+            run_bn254_3inputs(
+                circuit_filepath.to_string().clone(),
+                witness_gen_filepath.to_string(),
+                vec![F::<G2>::from(0)],
+                backend,
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "needs the native witness generator compiled from circom's C++ output, see README"]
+    fn test_bn254_3inputs_native_witness_generator() {
+        let manifest = CircuitManifest::load("circuits/3inputs/bn254/manifest.json");
+        let circuit_filepath = manifest.r1cs.to_str().unwrap();
+        let wasm = manifest
+            .witness_generator(WitnessGeneratorKind::Wasm)
+            .unwrap();
+        let native = manifest
+            .witness_generator(WitnessGeneratorKind::Native)
+            .unwrap();
+
+        let wasm_report = run_bn254_3inputs(
+            circuit_filepath.to_string(),
+            wasm.path.to_str().unwrap().to_string(),
+            vec![F::<G2>::from(0)],
+            CompressionBackend::Spartan,
+        );
+        let native_report = run_bn254_3inputs(
+            circuit_filepath.to_string(),
+            native.path.to_str().unwrap().to_string(),
+            vec![F::<G2>::from(0)],
+            CompressionBackend::Spartan,
+        );
+
+        assert_eq!(wasm_report.z_last, native_report.z_last);
+    }
+
+    #[test]
+    #[ignore = "needs circuits/array_inputs compiled with circom, see README"]
+    fn test_bn254_array_private_inputs() {
+        let manifest = CircuitManifest::load("circuits/array_inputs/manifest.json");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(manifest.r1cs.clone()));
+        let witness_generator = manifest
+            .witness_generator(WitnessGeneratorKind::Wasm)
+            .unwrap();
+
+        let shapes = manifest.signal_shapes().unwrap();
+        let private_inputs = load_private_inputs("circuits/array_inputs/private_inputs.json");
+        for private_input in &private_inputs {
+            shapes
+                .validate_complete::<F<G1>>(private_input, &["adders", "matrix"])
+                .unwrap();
+        }
+
+        let start_public_input = vec![F::<G1>::from(10), F::<G1>::from(20)];
+        let z0_secondary = vec![F::<G2>::from(0)];
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+        let (recursive_snark, _) = create_recursive_circuit(
+            &witness_generator,
+            r1cs,
+            private_inputs.clone(),
+            start_public_input.clone(),
+            z0_secondary.clone(),
+            &pp,
+            WitnessGeneration::Sequential,
+        )
+        .unwrap();
+
+        let (z_last, _) = recursive_snark
+            .verify(
+                &pp,
+                private_inputs.len(),
+                &start_public_input,
+                &z0_secondary,
+            )
+            .unwrap();
+        assert_eq!(
+            z_last,
+            predict_output(&ArrayInputs, &start_public_input, &private_inputs)
+        );
+    }
+
+    // Folds the circom circuit and its Rust twin from native_circuits.rs over the same inputs,
+    // printing both constraint counts and fold times.
+    fn compare_native_and_circom<C>(r1cs: &str, wasm: &str, start_public_input: Vec<F<G1>>)
+    where
+        C: StepCircuit<F<G1>> + From<F<G1>> + Default,
+    {
+        let iteration_count = 8;
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(5))]); iteration_count];
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(r1cs.into()));
+        let circom_pp: PublicParams<G1, G2, _, _> = create_public_params(r1cs.clone());
+        let circom_circuits = generate_circuits_sequential(
+            &WitnessGenerator::from_path(wasm),
+            &r1cs,
+            &private_inputs,
+            &start_public_input,
+        );
+        let (circom_snark, circom_trace) = fold(
+            &circom_pp,
+            &circom_circuits,
+            &start_public_input,
+            &z0_secondary,
+        )
+        .unwrap();
+
+        let native_pp =
+            PublicParams::<G1, G2, C, C2<G2>>::setup(&C::default(), &TrivialTestCircuit::default());
+        let native_circuits = adder_step_circuits::<F<G1>, C>(&private_inputs);
+        let (native_snark, native_trace) = fold(
+            &native_pp,
+            &native_circuits,
+            &start_public_input,
+            &z0_secondary,
+        )
+        .unwrap();
+
+        let fold_time = |trace: &FoldingTrace| -> u128 {
+            trace.steps.iter().map(|record| record.fold_us).sum()
+        };
+        println!(
+            "{}: circom {} constraints, folded in {}us; native {} constraints, folded in {}us",
+            std::any::type_name::<C>(),
+            circom_pp.num_constraints().0,
+            fold_time(&circom_trace),
+            native_pp.num_constraints().0,
+            fold_time(&native_trace)
+        );
+
+        let (circom_z_last, _) = circom_snark
+            .verify(
+                &circom_pp,
+                iteration_count,
+                &start_public_input,
+                &z0_secondary,
+            )
+            .unwrap();
+        let (native_z_last, _) = native_snark
+            .verify(
+                &native_pp,
+                iteration_count,
+                &start_public_input,
+                &z0_secondary,
+            )
+            .unwrap();
+        assert_eq!(circom_z_last, native_z_last);
+    }
+
+    #[test]
+    fn native_2inputs_matches_circom() {
+        compare_native_and_circom::<TwoInputsCircuit<F<G1>>>(
+            "circuits/2inputs/bn254/bn254.r1cs",
+            "circuits/2inputs/bn254/bn254.wasm",
+            vec![F::<G1>::from(10), F::<G1>::from(10)],
+        );
+    }
+
+    #[test]
+    fn native_3inputs_matches_circom() {
+        compare_native_and_circom::<ThreeInputsCircuit<F<G1>>>(
+            "circuits/3inputs/bn254/3inputs.r1cs",
+            "circuits/3inputs/bn254/3inputs.wasm",
+            vec![F::<G1>::from(4), F::<G1>::from(7), F::<G1>::from(8)],
+        );
+    }
+
+    #[test]
+    fn native_poseidon_circuit_folds() {
+        let iteration_count = 4;
+        let private_inputs =
+            vec![HashMap::from([("priv_hash_input".to_string(), json!(9))]); iteration_count];
+        let start_public_input = vec![F::<G1>::from(5), F::<G1>::from(77)];
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let pp = PublicParams::<G1, G2, PoseidonCircuit<F<G1>>, C2<G2>>::setup(
+            &PoseidonCircuit::default(),
+            &TrivialTestCircuit::default(),
+        );
+        let circom_pp: PublicParams<G1, G2, _, _> = create_public_params(load_r1cs::<G1, G2>(
+            &FileLocation::PathBuf("circuits/poseidon/poseidon_hash.r1cs".into()),
+        ));
+        println!(
+            "Constraints per step: circom (circomlib Poseidon) {}, Rust (nova's Poseidon) {}",
+            circom_pp.num_constraints().0,
+            pp.num_constraints().0
+        );
+
+        let step_circuits = poseidon_step_circuits(&private_inputs);
+        let (recursive_snark, _) =
+            fold(&pp, &step_circuits, &start_public_input, &z0_secondary).unwrap();
+        let (z_last, _) = recursive_snark
+            .verify(&pp, iteration_count, &start_public_input, &z0_secondary)
+            .unwrap();
+
+        let mut expected = start_public_input;
+        for circuit in &step_circuits.circuits {
+            expected = circuit.output(&expected);
+        }
+        assert_eq!(z_last, expected);
+    }
+
+    #[test]
+    fn nonuniform_mixed_program() {
+        let program = [ADD, HASH, HASH, ADD, HASH, ADD, ADD];
+        let private_inputs: Vec<_> = (0..program.len())
+            .map(|i| HashMap::from([("adder".to_string(), json!(i))]))
+            .collect();
+        // The last element is the program accumulator, it starts at zero.
+        let start_public_input = vec![
+            F::<G1>::from(4),
+            F::<G1>::from(7),
+            F::<G1>::from(8),
+            F::<G1>::from(0),
+        ];
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let pp = PublicParams::<G1, G2, NonUniformCircuit<F<G1>, AddOrHash<F<G1>>>, C2<G2>>::setup(
+            &NonUniformCircuit::default(),
+            &TrivialTestCircuit::default(),
+        );
+        println!(
+            "Constraints per step with every instruction synthesized: {}",
+            pp.num_constraints().0
+        );
+
+        let step_circuits =
+            program_step_circuits(&program, AddOrHash::from_private_inputs(&private_inputs));
+        let (recursive_snark, _) =
+            fold(&pp, &step_circuits, &start_public_input, &z0_secondary).unwrap();
+        let (z_last, _) = recursive_snark
+            .verify(&pp, program.len(), &start_public_input, &z0_secondary)
+            .unwrap();
+
+        let mut expected = start_public_input;
+        for circuit in &step_circuits.circuits {
+            expected = circuit.output(&expected);
+        }
+        assert_eq!(z_last, expected);
+        assert_eq!(
+            z_last[3],
+            encode_program(AddOrHash::<F<G1>>::OPCODES, &program)
+        );
+        assert_ne!(
+            z_last[3],
+            encode_program(
+                AddOrHash::<F<G1>>::OPCODES,
+                &[ADD, HASH, HASH, ADD, HASH, ADD, HASH]
+            )
+        );
+    }
+
+    #[test]
+    fn unrolled_2inputs_matches_model() {
+        let (k, steps) = (4, 8);
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/2inputs/bn254/bn254.r1cs".into(),
+        ));
+        let private_inputs: Vec<_> = (0..steps)
+            .map(|i| HashMap::from([("adder".to_string(), json!(i))]))
+            .collect();
+        let start_public_input = vec![F::<G1>::from(10), F::<G1>::from(10)];
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let setup_circuit = CircomCircuit {
+            r1cs: r1cs.clone(),
+            witness: None,
+        };
+        let pp = PublicParams::<G1, G2, Unrolled<C1<G1>>, C2<G2>>::setup(
+            &Unrolled::setup_circuit(setup_circuit, k),
+            &TrivialTestCircuit::default(),
+        );
+
+        let step_circuits = unroll(
+            generate_circuits_sequential(
+                &WitnessGenerator::from_path("circuits/2inputs/bn254/bn254.wasm"),
+                &r1cs,
+                &private_inputs,
+                &start_public_input,
+            ),
+            k,
+        );
+        assert_eq!(step_circuits.circuits.len(), steps / k);
+
+        let (recursive_snark, _) =
+            fold(&pp, &step_circuits, &start_public_input, &z0_secondary).unwrap();
+        let (z_last, _) = recursive_snark
+            .verify(&pp, steps / k, &start_public_input, &z0_secondary)
+            .unwrap();
+        assert_eq!(
+            z_last,
+            predict_output(&TwoInputs, &start_public_input, &private_inputs)
+        );
+    }
+
+    // Accumulate then hash, out of the two existing circom circuits.
+    #[test]
+    fn composite_circom_2inputs_then_poseidon() {
+        let iteration_count = 3;
+        let private_input = HashMap::from([
+            ("first.adder".to_string(), json!(3)),
+            ("second.priv_hash_input".to_string(), json!(9)),
+        ]);
+        let private_inputs = vec![private_input; iteration_count];
+        let start_public_input = vec![F::<G1>::from(10), F::<G1>::from(10)];
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let load = |path: &str| load_r1cs::<G1, G2>(&FileLocation::PathBuf(path.into()));
+        let adder_r1cs = load("circuits/2inputs/bn254/bn254.r1cs");
+        let poseidon_r1cs = load("circuits/poseidon/poseidon_hash.r1cs");
+        let adder = WitnessGenerator::from_path("circuits/2inputs/bn254/bn254.wasm");
+        let poseidon = WitnessGenerator::from_path("circuits/poseidon/poseidon_hash.wasm");
+
+        let setup_circuit = |r1cs: &R1CS<F<G1>>| CircomCircuit {
+            r1cs: r1cs.clone(),
+            witness: None,
+        };
+        let pp = PublicParams::<G1, G2, Composite<C1<G1>, C1<G1>>, C2<G2>>::setup(
+            &Composite::new(setup_circuit(&adder_r1cs), setup_circuit(&poseidon_r1cs)).unwrap(),
+            &TrivialTestCircuit::default(),
+        );
+
+        let step_circuits = generate_composite_circuits_sequential(
+            (&adder, &adder_r1cs),
+            (&poseidon, &poseidon_r1cs),
+            &private_inputs,
+            &start_public_input,
+        )
+        .unwrap();
+        let (recursive_snark, _) =
+            fold(&pp, &step_circuits, &start_public_input, &z0_secondary).unwrap();
+        let (z_last, _) = recursive_snark
+            .verify(&pp, iteration_count, &start_public_input, &z0_secondary)
+            .unwrap();
+
+        let model = Composed {
+            first: &TwoInputs,
+            second: &PoseidonHash,
+        };
+        assert_eq!(
+            z_last,
+            predict_output(&model, &start_public_input, &private_inputs)
+        );
+    }
+
+    // Hash then accumulate, out of the two Rust circuits.
+    #[test]
+    fn composite_native_poseidon_then_2inputs() {
+        let iteration_count = 3;
+        let private_input = HashMap::from([
+            ("first.priv_hash_input".to_string(), json!(9)),
+            ("second.adder".to_string(), json!(3)),
+        ]);
+        let private_inputs = vec![private_input; iteration_count];
+        let start_public_input = vec![F::<G1>::from(5), F::<G1>::from(77)];
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let first: Vec<_> = private_inputs
+            .iter()
+            .map(|input| component_private_input(input, "first"))
+            .collect();
+        let second: Vec<_> = private_inputs
+            .iter()
+            .map(|input| component_private_input(input, "second"))
+            .collect();
+        let step_circuits = compose(
+            poseidon_step_circuits(&first),
+            adder_step_circuits::<F<G1>, TwoInputsCircuit<F<G1>>>(&second),
+        )
+        .unwrap();
+
+        let pp = PublicParams::<
+            G1,
+            G2,
+            Composite<PoseidonCircuit<F<G1>>, TwoInputsCircuit<F<G1>>>,
+            C2<G2>,
+        >::setup(
+            &Composite::new(PoseidonCircuit::default(), TwoInputsCircuit::default()).unwrap(),
+            &TrivialTestCircuit::default(),
+        );
+        let (recursive_snark, _) =
+            fold(&pp, &step_circuits, &start_public_input, &z0_secondary).unwrap();
+        let (z_last, _) = recursive_snark
+            .verify(&pp, iteration_count, &start_public_input, &z0_secondary)
+            .unwrap();
+
+        let mut expected = start_public_input;
+        for (circuit, private_input) in step_circuits.circuits.iter().zip(&second) {
+            expected = TwoInputs.step(&circuit.first.output(&expected), private_input);
+        }
+        assert_eq!(z_last, expected);
+    }
+
+    #[test]
+    fn composite_rejects_different_arities() {
+        let composite = Composite::new(
+            TwoInputsCircuit::<F<G1>>::default(),
+            ThreeInputsCircuit::<F<G1>>::default(),
+        );
+
+        assert_eq!(
+            composite.err(),
+            Some(CompositeError::ArityMismatch {
+                first: 2,
+                second: 3
+            })
+        );
+    }
+
+    #[test]
+    fn compose_rejects_different_step_counts() {
+        let private_input = HashMap::from([("adder".to_string(), json!(3))]);
+        let composed = compose(
+            adder_step_circuits::<F<G1>, TwoInputsCircuit<F<G1>>>(&[private_input.clone()]),
+            adder_step_circuits::<F<G1>, TwoInputsCircuit<F<G1>>>(&[
+                private_input.clone(),
+                private_input,
+            ]),
+        );
+
+        assert_eq!(
+            composed.err(),
+            Some(CompositeError::StepCountMismatch {
+                first: 1,
+                second: 2
+            })
+        );
+    }
+
+    #[test]
+    fn linear_step_witness_satisfies_its_r1cs() {
+        let step = LinearStep::<F<G1>>::new(2, &["a", "b"])
+            .output(&[2, -1, 0, 3])
+            .output(&[0, 1, -5, 1]);
+        let private_input = HashMap::from([
+            ("a".to_string(), json!(-7)),
+            ("b".to_string(), json!("0x10")),
+        ]);
+        let witness = step.witness(&[F::<G1>::from(4), F::<G1>::from(9)], &private_input);
+
+        let r1cs = step.r1cs();
+        assert_eq!(witness.len(), r1cs.num_variables);
+        let eval =
+            |lc: &Vec<(usize, F<G1>)>| -> F<G1> { lc.iter().map(|(i, c)| witness[*i] * c).sum() };
+        for (a, b, c) in &r1cs.constraints {
+            assert_eq!(eval(a) * eval(b), eval(c));
+        }
+        // 2 * 4 - 9 + 3 * 16 and 9 + 5 * 7 + 16
+        assert_eq!(witness[1..3], [F::<G1>::from(47), F::<G1>::from(60)]);
+    }
+
+    // Folds `step` through its in-Rust R1CS and witnesses and returns the verified z_n.
+    fn fold_linear_step(
+        step: &LinearStep<F<G1>>,
+        private_inputs: &[HashMap<String, serde_json::Value>],
+        start_public_input: &[F<G1>],
+    ) -> Vec<F<G1>> {
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let pp: PublicParams<G1, G2, _, _> = create_public_params(step.r1cs());
+        let step_circuits = step.step_circuits(private_inputs, start_public_input);
+        let (recursive_snark, _) =
+            fold(&pp, &step_circuits, start_public_input, &z0_secondary).unwrap();
+        let (z_last, _) = recursive_snark
+            .verify(&pp, private_inputs.len(), start_public_input, &z0_secondary)
+            .unwrap();
+
+        z_last
+    }
+
+    #[test]
+    fn linear_2inputs_folds_without_circom() {
+        let iteration_count = 5;
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(3))]); iteration_count];
+        let start_public_input = vec![F::<G1>::from(10), F::<G1>::from(10)];
+
+        assert_eq!(
+            fold_linear_step(
+                &LinearStep::two_inputs(),
+                &private_inputs,
+                &start_public_input
+            ),
+            predict_output(&TwoInputs, &start_public_input, &private_inputs)
+        );
+    }
+
+    #[test]
+    fn linear_3inputs_folds_without_circom() {
+        let iteration_count = 5;
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(9))]); iteration_count];
+        let start_public_input = vec![F::<G1>::from(4), F::<G1>::from(7), F::<G1>::from(8)];
+
+        assert_eq!(
+            fold_linear_step(
+                &LinearStep::three_inputs(),
+                &private_inputs,
+                &start_public_input
+            ),
+            predict_output(&ThreeInputs, &start_public_input, &private_inputs)
+        );
+    }
+
+    #[test]
+    fn affine_closed_form_matches_replaying_the_steps() {
+        let private_input = HashMap::from([("adder".to_string(), json!(-3))]);
+        let start = [F::<G1>::from(4), F::<G1>::from(7), F::<G1>::from(8)];
+
+        let models: [(&dyn StepModel<F<G1>>, Affine<F<G1>>); 3] = [
+            (&TwoInputs, TwoInputs::affine(&private_input)),
+            (&ThreeInputs, ThreeInputs::affine(&private_input)),
+            (&TwoInputs, LinearStep::two_inputs().affine(&private_input)),
+        ];
+        for (model, affine) in models {
+            let start = &start[..affine.offset.len()];
+            for n in 0..20 {
+                let private_inputs = vec![private_input.clone(); n];
+                assert_eq!(
+                    affine.output_after(start, n as u64),
+                    predict_output(model, start, &private_inputs)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "one value per state element")]
+    fn affine_closed_form_rejects_a_start_of_another_arity() {
+        let private_input = HashMap::from([("adder".to_string(), json!(3))]);
+
+        ThreeInputs::affine(&private_input).output_after(&[F::<G1>::from(4), F::<G1>::from(7)], 1);
+    }
+
+    #[test]
+    fn affine_closed_form_for_millions_of_steps() {
+        let n: u64 = 3_000_000;
+        let private_input = HashMap::from([("adder".to_string(), json!(3))]);
+
+        // 2inputs: step_in[0] grows by the adder, step_in[1] adds up step_in[0], so
+        // z_n = (s0 + n * adder, s1 + n * s0 + adder * n * (n - 1) / 2).
+        let (s0, s1, adder) = (F::<G1>::from(10), F::<G1>::from(10), F::<G1>::from(3));
+        let n_f = F::<G1>::from(n);
+        let triangle = F::<G1>::from(n * (n - 1) / 2);
+        assert_eq!(
+            TwoInputs::affine(&private_input).output_after(&[s0, s1], n),
+            vec![s0 + n_f * adder, s1 + n_f * s0 + adder * triangle]
+        );
+    }
+
+    // Replaying stays cheap at a few thousand steps, enough for the powers of the matrix to be
+    // built from many squarings.
+    #[test]
+    fn affine_closed_form_matches_thousands_of_replayed_steps() {
+        let n: u64 = 5_000;
+        let private_input = HashMap::from([("adder".to_string(), json!(3))]);
+
+        let start = [F::<G1>::from(4), F::<G1>::from(7), F::<G1>::from(8)];
+        let mut z = start.to_vec();
+        for _ in 0..n {
+            z = ThreeInputs.step(&z, &private_input);
+        }
+        assert_eq!(
+            ThreeInputs::affine(&private_input).output_after(&start, n),
+            z
         );
     }
 
@@ -50,10 +707,14 @@ mod test {
         let circuit_filepath = "circuits/poseidon/poseidon_hash.r1cs";
         let witness_gen_filepath = "circuits/poseidon/poseidon_hash.wasm";
 
-        poseidon(
-            circuit_filepath.to_string().clone(),
-            witness_gen_filepath.to_string(),
-        );
+        for backend in CompressionBackend::ALL {
+            poseidon(
+                circuit_filepath.to_string().clone(),
+                witness_gen_filepath.to_string(),
+                vec![F::<G2>::from(0)],
+                backend,
+            );
+        }
     }
 
     #[test]
@@ -61,9 +722,919 @@ mod test {
         let circuit_filepath = "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.r1cs";
         let witness_gen_filepath = "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.wasm";
 
-        poseidon_2_priv_inputs(
-            circuit_filepath.to_string().clone(),
-            witness_gen_filepath.to_string(),
+        for backend in CompressionBackend::ALL {
+            poseidon_2_priv_inputs(
+                circuit_filepath.to_string().clone(),
+                witness_gen_filepath.to_string(),
+                vec![F::<G2>::from(0)],
+                backend,
+            );
+        }
+    }
+
+    #[test]
+    fn test_bn254_3inputs_parallel_witness_generation() {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/3inputs/bn254/3inputs.r1cs".into(),
+        ));
+        let witness_generator = WitnessGenerator::from_path("circuits/3inputs/bn254/3inputs.wasm");
+
+        let iteration_count = 4;
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(9))]); iteration_count];
+        let start_public_input = vec![F::<G1>::from(4), F::<G1>::from(7), F::<G1>::from(8)];
+
+        let pp: PublicParams<G1, G2, _, _> = create_public_params(r1cs.clone());
+        let (recursive_snark, _) = create_recursive_circuit(
+            &witness_generator,
+            r1cs,
+            private_inputs,
+            start_public_input.clone(),
+            vec![F::<G2>::from(0)],
+            &pp,
+            WitnessGeneration::Parallel(&ThreeInputs),
+        )
+        .unwrap();
+
+        let z0_secondary = [F::<G2>::from(0)];
+        let (z_last, _) = recursive_snark
+            .verify(&pp, iteration_count, &start_public_input, &z0_secondary)
+            .unwrap();
+
+        assert_eq!(
+            z_last,
+            vec![F::<G1>::from(360), F::<G1>::from(560), F::<G1>::from(551)]
+        );
+    }
+
+    #[test]
+    fn test_bn254_2inputs_secondary_z0_mismatch() {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/2inputs/bn254/bn254.r1cs".into(),
+        ));
+        let witness_generator = WitnessGenerator::from_path("circuits/2inputs/bn254/bn254.wasm");
+
+        let iteration_count = 3;
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(3))]); iteration_count];
+        let start_public_input = vec![F::<G1>::from(10), F::<G1>::from(10)];
+        let z0_secondary = vec![F::<G2>::from(5)];
+        let wrong_z0_secondary = vec![F::<G2>::from(0)];
+
+        let pp: PublicParams<G1, G2, _, _> = create_public_params(r1cs.clone());
+        let (recursive_snark, _) = create_recursive_circuit(
+            &witness_generator,
+            r1cs,
+            private_inputs,
+            start_public_input.clone(),
+            z0_secondary.clone(),
+            &pp,
+            WitnessGeneration::Sequential,
+        )
+        .unwrap();
+
+        let (_, z_last_secondary) = recursive_snark
+            .verify(&pp, iteration_count, &start_public_input, &z0_secondary)
+            .unwrap();
+        assert_eq!(z_last_secondary, z0_secondary);
+        assert!(recursive_snark
+            .verify(
+                &pp,
+                iteration_count,
+                &start_public_input,
+                &wrong_z0_secondary
+            )
+            .is_err());
+
+        for backend in CompressionBackend::ALL {
+            let keys = CompressionKeys::setup(backend, &pp).unwrap();
+            let compressed_snark = keys.prove(&pp, &recursive_snark).unwrap();
+
+            assert!(compressed_snark
+                .verify(
+                    &keys,
+                    iteration_count,
+                    start_public_input.clone(),
+                    z0_secondary.clone()
+                )
+                .is_ok());
+            assert!(compressed_snark
+                .verify(
+                    &keys,
+                    iteration_count,
+                    start_public_input.clone(),
+                    wrong_z0_secondary.clone()
+                )
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_bn254_2inputs_folding_trace() {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/2inputs/bn254/bn254.r1cs".into(),
+        ));
+        let witness_generator = WitnessGenerator::from_path("circuits/2inputs/bn254/bn254.wasm");
+
+        let iteration_count = 8;
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(3))]); iteration_count];
+        let start_public_input = vec![F::<G1>::from(10), F::<G1>::from(10)];
+
+        let pp: PublicParams<G1, G2, _, _> = create_public_params(r1cs.clone());
+        let (_, trace) = create_recursive_circuit(
+            &witness_generator,
+            r1cs,
+            private_inputs,
+            start_public_input,
+            vec![F::<G2>::from(0)],
+            &pp,
+            WitnessGeneration::Sequential,
+        )
+        .unwrap();
+
+        assert_eq!(trace.steps.len(), iteration_count);
+        assert!(trace
+            .steps
+            .iter()
+            .enumerate()
+            .all(|(i, record)| record.step == i));
+        // Step 0 is proved by RecursiveSNARK::new.
+        assert!(trace.steps[0].fold_us > 0);
+
+        let csv = trace.to_csv();
+        assert_eq!(csv.lines().count(), iteration_count + 1);
+        assert!(csv.starts_with("step,witness_generation_us,fold_us,resident_memory_bytes"));
+
+        let json: serde_json::Value = serde_json::from_str(&trace.to_json()).unwrap();
+        assert_eq!(json["steps"].as_array().unwrap().len(), iteration_count);
+    }
+
+    #[test]
+    fn fold_without_steps() {
+        let pp = PublicParams::<G1, G2, TwoInputsCircuit<F<G1>>, C2<G2>>::setup(
+            &TwoInputsCircuit::default(),
+            &TrivialTestCircuit::default(),
+        );
+        let step_circuits = adder_step_circuits::<F<G1>, TwoInputsCircuit<_>>(&[]);
+
+        let result = fold(
+            &pp,
+            &step_circuits,
+            &[F::<G1>::from(10), F::<G1>::from(10)],
+            &[F::<G2>::from(0)],
+        );
+
+        assert!(matches!(result, Err(NovaError::InvalidNumSteps)));
+    }
+
+    // The table in the comments of bn254_3_inputs.rs.
+    #[test]
+    fn test_bn254_3inputs_trajectory() {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/3inputs/bn254/3inputs.r1cs".into(),
+        ));
+        let witness_generator = WitnessGenerator::from_path("circuits/3inputs/bn254/3inputs.wasm");
+
+        let iteration_count = 4;
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(9))]); iteration_count];
+        let start_public_input = vec![F::<G1>::from(4), F::<G1>::from(7), F::<G1>::from(8)];
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let pp: PublicParams<G1, G2, _, _> = create_public_params(r1cs.clone());
+        let step_circuits = generate_circuits(
+            &witness_generator,
+            &r1cs,
+            &private_inputs,
+            &start_public_input,
+            WitnessGeneration::Parallel(&ThreeInputs),
+        );
+        let (recursive_snark, _) =
+            fold(&pp, &step_circuits, &start_public_input, &z0_secondary).unwrap();
+        let trajectory = Trajectory::from_circuits(&start_public_input, &step_circuits);
+
+        assert_eq!(
+            trajectory.to_csv(),
+            "step,z[0],z[1],z[2]\n\
+             0,4,7,8\n\
+             1,20,28,19\n\
+             2,57,76,67\n\
+             3,142,209,200\n\
+             4,360,560,551\n"
+        );
+
+        let (z_last, _) = recursive_snark
+            .verify(&pp, iteration_count, &start_public_input, &z0_secondary)
+            .unwrap();
+        assert_eq!(trajectory.state::<F<G1>>(iteration_count), z_last);
+
+        let json: serde_json::Value = serde_json::from_str(&trajectory.to_json()).unwrap();
+        assert_eq!(json["states"][1], json!(["20", "28", "19"]));
+    }
+
+    #[test]
+    fn introspect_relaxed_instances_of_native_2inputs() {
+        let iteration_count = 4;
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(3))]); iteration_count];
+        let start_public_input = vec![F::<G1>::from(10), F::<G1>::from(10)];
+        let z0_secondary = vec![F::<G2>::from(0)];
+
+        let pp = PublicParams::<G1, G2, TwoInputsCircuit<F<G1>>, C2<G2>>::setup(
+            &TwoInputsCircuit::default(),
+            &TrivialTestCircuit::default(),
+        );
+        let step_circuits = adder_step_circuits::<F<G1>, TwoInputsCircuit<_>>(&private_inputs);
+        let (recursive_snark, _, introspection) =
+            fold_introspected(&pp, &step_circuits, &start_public_input, &z0_secondary).unwrap();
+        recursive_snark
+            .verify(&pp, iteration_count, &start_public_input, &z0_secondary)
+            .unwrap();
+
+        let steps = &introspection.steps;
+        assert_eq!(steps.len(), iteration_count);
+
+        // Nothing is folded by the first step, the running instance is still the default one.
+        assert_eq!(steps[0].primary.u, "0");
+        // Afterwards u is a random combination, different at every step.
+        for (previous, record) in steps.iter().zip(&steps[1..]) {
+            assert_ne!(record.primary.u, "0");
+            assert_ne!(record.primary.u, "1");
+            assert_ne!(record.primary.u, previous.primary.u);
+            assert_ne!(record.primary.comm_w, previous.primary.comm_w);
+        }
+        assert!(steps.iter().all(|record| record.primary.x.len() == 2));
+
+        let csv = introspection.to_csv();
+        assert_eq!(csv.lines().count(), iteration_count + 1);
+        assert!(csv.starts_with("step,primary_u,primary_x,"));
+    }
+
+    // Folds the wasm witnesses of every step with relaxed_r1cs.rs, checks the folded instance is
+    // satisfied and returns its error term E.
+    fn reference_fold_error_term(
+        r1cs: &str,
+        wasm: &str,
+        private_inputs: &[HashMap<String, serde_json::Value>],
+        start_public_input: &[F<G1>],
+    ) -> Vec<F<G1>> {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(r1cs.into()));
+        let witnesses: Vec<_> = generate_circuits_sequential(
+            &WitnessGenerator::from_path(wasm),
+            &r1cs,
+            private_inputs,
+            start_public_input,
+        )
+        .circuits
+        .into_iter()
+        .map(|circuit| circuit.witness.unwrap())
+        .collect();
+
+        let ck = CommitmentKey::for_r1cs(&r1cs);
+        let (instance, witness) = fold_witnesses(&ck, &r1cs, &witnesses);
+        assert_eq!(is_satisfied(&ck, &r1cs, &instance, &witness), Ok(()));
+
+        witness.e
+    }
+
+    #[test]
+    fn reference_folding_of_circom_circuits() {
+        let zero = F::<G1>::from(0);
+        let adders = |adder| vec![HashMap::from([("adder".to_string(), json!(adder))]); 4];
+
+        // The adders only have linear constraints, nothing goes into E.
+        assert!(reference_fold_error_term(
+            "circuits/2inputs/bn254/bn254.r1cs",
+            "circuits/2inputs/bn254/bn254.wasm",
+            &adders(3),
+            &[F::<G1>::from(10), F::<G1>::from(10)],
+        )
+        .iter()
+        .all(|e| *e == zero));
+        assert!(reference_fold_error_term(
+            "circuits/3inputs/bn254/3inputs.r1cs",
+            "circuits/3inputs/bn254/3inputs.wasm",
+            &adders(9),
+            &[F::<G1>::from(4), F::<G1>::from(7), F::<G1>::from(8)],
+        )
+        .iter()
+        .all(|e| *e == zero));
+
+        for (r1cs, wasm) in [
+            (
+                "circuits/poseidon/poseidon_hash.r1cs",
+                "circuits/poseidon/poseidon_hash.wasm",
+            ),
+            (
+                "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.r1cs",
+                "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.wasm",
+            ),
+        ] {
+            let golden = Golden::load(&Golden::path_for(Path::new(r1cs)));
+            let (start_public_input, private_inputs) = golden.inputs::<F<G1>>();
+            assert!(
+                reference_fold_error_term(r1cs, wasm, &private_inputs, &start_public_input)
+                    .iter()
+                    .any(|e| *e != zero)
+            );
+        }
+    }
+
+    // Checks the wasm witness of every step against the R1CS, as a fresh relaxed instance, and
+    // against its CCS. A wrong step_out[0] has to break the same constraint in both forms.
+    fn check_r1cs_and_ccs<G1Cycle, G2Cycle>(
+        r1cs: &str,
+        wasm: &str,
+        private_inputs: &[HashMap<String, serde_json::Value>],
+        start_public_input: &[F<G1Cycle>],
+    ) where
+        G1Cycle: Group<Base = <G2Cycle as Group>::Scalar>,
+        G2Cycle: Group<Base = <G1Cycle as Group>::Scalar>,
+    {
+        let r1cs = load_r1cs::<G1Cycle, G2Cycle>(&FileLocation::PathBuf(r1cs.into()));
+        let ccs = Ccs::from_r1cs(&r1cs);
+        let ck = CommitmentKey::for_r1cs(&r1cs);
+        let step_circuits = generate_circuits_sequential(
+            &WitnessGenerator::from_path(wasm),
+            &r1cs,
+            private_inputs,
+            start_public_input,
+        );
+
+        for circuit in step_circuits.circuits {
+            let mut z = circuit.witness.unwrap();
+            let (instance, witness) = RelaxedR1CSWitness::from_circom(&ck, &r1cs, &z);
+            assert_eq!(is_satisfied(&ck, &r1cs, &instance, &witness), Ok(()));
+            assert_eq!(ccs.is_satisfied(&z), Ok(()));
+
+            z[1] += F::<G1Cycle>::from(1);
+            let (instance, witness) = RelaxedR1CSWitness::from_circom(&ck, &r1cs, &z);
+            match is_satisfied(&ck, &r1cs, &instance, &witness) {
+                Err(Unsatisfied::Constraint(row)) => assert_eq!(ccs.is_satisfied(&z), Err(row)),
+                result => panic!("wrong step_out[0] not caught by the R1CS: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn circom_circuits_satisfy_their_r1cs_and_ccs() {
+        let adders = |adder| vec![HashMap::from([("adder".to_string(), json!(adder))]); 3];
+
+        check_r1cs_and_ccs::<G1, G2>(
+            "circuits/2inputs/bn254/bn254.r1cs",
+            "circuits/2inputs/bn254/bn254.wasm",
+            &adders(3),
+            &[F::<G1>::from(10), F::<G1>::from(10)],
+        );
+        type G1Pasta = pasta_curves::vesta::Point;
+        type G2Pasta = pasta_curves::pallas::Point;
+        check_r1cs_and_ccs::<G1Pasta, G2Pasta>(
+            "circuits/2inputs/pasta/pasta.r1cs",
+            "circuits/2inputs/pasta/pasta.wasm",
+            &adders(3),
+            &[F::<G1Pasta>::from(10), F::<G1Pasta>::from(10)],
+        );
+        check_r1cs_and_ccs::<G1, G2>(
+            "circuits/3inputs/bn254/3inputs.r1cs",
+            "circuits/3inputs/bn254/3inputs.wasm",
+            &adders(9),
+            &[F::<G1>::from(4), F::<G1>::from(7), F::<G1>::from(8)],
+        );
+
+        for (r1cs, wasm) in [
+            (
+                "circuits/poseidon/poseidon_hash.r1cs",
+                "circuits/poseidon/poseidon_hash.wasm",
+            ),
+            (
+                "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.r1cs",
+                "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.wasm",
+            ),
+        ] {
+            let golden = Golden::load(&Golden::path_for(Path::new(r1cs)));
+            let (start_public_input, private_inputs) = golden.inputs::<F<G1>>();
+            check_r1cs_and_ccs::<G1, G2>(r1cs, wasm, &private_inputs, &start_public_input);
+        }
+    }
+
+    #[test]
+    #[ignore = "needs circuits/array_inputs compiled with circom, see README"]
+    fn array_inputs_satisfy_their_r1cs_and_ccs() {
+        let manifest = CircuitManifest::load("circuits/array_inputs/manifest.json");
+        let wasm = manifest
+            .witness_generator(WitnessGeneratorKind::Wasm)
+            .unwrap();
+
+        check_r1cs_and_ccs::<G1, G2>(
+            manifest.r1cs.to_str().unwrap(),
+            wasm.path.to_str().unwrap(),
+            &load_private_inputs("circuits/array_inputs/private_inputs.json"),
+            &[F::<G1>::from(10), F::<G1>::from(20)],
+        );
+    }
+
+    // Replays the golden files through the native models, so they can be checked without circom.
+    #[test]
+    fn poseidon_golden_files_match_native_models() {
+        let circuits: [(&str, &dyn StepModel<F<G1>>); 2] = [
+            ("circuits/poseidon/poseidon_hash.r1cs", &PoseidonHash),
+            (
+                "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.r1cs",
+                &Poseidon2PrivInputs,
+            ),
+        ];
+
+        for (r1cs, model) in circuits {
+            let golden = Golden::load(&Golden::path_for(Path::new(r1cs)));
+            let (start_public_input, private_inputs) = golden.inputs::<F<G1>>();
+            assert_eq!(golden.iteration_count, private_inputs.len());
+
+            let z_last = predict_output(model, &start_public_input, &private_inputs);
+            assert_eq!(
+                Golden::new(&start_public_input, &private_inputs, &z_last),
+                golden
+            );
+        }
+    }
+
+    #[test]
+    fn poseidon_witness_generation_timings() {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/poseidon/poseidon_hash.r1cs".into(),
+        ));
+        let witness_generator = WitnessGenerator::from_path("circuits/poseidon/poseidon_hash.wasm");
+
+        let private_inputs = vec![HashMap::from([("priv_hash_input".to_string(), json!(9))]); 16];
+        let start_public_input = [F::<G1>::from(5), F::<G1>::from(77)];
+
+        time_witness_generation(
+            &witness_generator,
+            &r1cs,
+            &private_inputs,
+            &start_public_input,
+        );
+    }
+
+    #[test]
+    fn poseidon_2_priv_inputs_witness_generation_timings() {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.r1cs".into(),
+        ));
+        let witness_generator = WitnessGenerator::from_path(
+            "circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.wasm",
+        );
+
+        let private_input = HashMap::from([
+            ("priv_hash_1".to_string(), json!(77)),
+            ("priv_hash_2".to_string(), json!(9)),
+        ]);
+        let private_inputs = vec![private_input; 16];
+        let start_public_input = [F::<G1>::from(5)];
+
+        time_witness_generation(
+            &witness_generator,
+            &r1cs,
+            &private_inputs,
+            &start_public_input,
+        );
+    }
+
+    #[test]
+    fn witness_generation_timings_without_steps() {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/2inputs/bn254/bn254.r1cs".into(),
+        ));
+        let witness_generator = WitnessGenerator::from_path("circuits/2inputs/bn254/bn254.wasm");
+
+        time_witness_generation(
+            &witness_generator,
+            &r1cs,
+            &[],
+            &[F::<G1>::from(10), F::<G1>::from(10)],
+        );
+    }
+
+    /*
+    Negative tests: the verifiers have to reject anything that does not match the folded run. They
+    all start from the 2inputs circuit folded for a few steps.
+    */
+    fn fold_2inputs<G1Cycle, G2Cycle>(
+        circuit_filepath: &str,
+        witness_gen_filepath: &str,
+        iteration_count: usize,
+    ) -> (
+        PublicParams<G1Cycle, G2Cycle, C1<G1Cycle>, C2<G2Cycle>>,
+        RecursiveSNARK<G1Cycle, G2Cycle, C1<G1Cycle>, C2<G2Cycle>>,
+        Vec<F<G1Cycle>>,
+        Vec<F<G2Cycle>>,
+    )
+    where
+        G1Cycle: Group<Base = <G2Cycle as Group>::Scalar>,
+        G2Cycle: Group<Base = <G1Cycle as Group>::Scalar>,
+    {
+        let r1cs = load_r1cs::<G1Cycle, G2Cycle>(&FileLocation::PathBuf(circuit_filepath.into()));
+        let witness_generator = WitnessGenerator::from_path(witness_gen_filepath);
+
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(3))]); iteration_count];
+        let start_public_input = vec![F::<G1Cycle>::from(10), F::<G1Cycle>::from(10)];
+        let z0_secondary = vec![F::<G2Cycle>::from(0)];
+
+        let pp = create_public_params::<G1Cycle, G2Cycle>(r1cs.clone());
+        let (recursive_snark, _) = create_recursive_circuit(
+            &witness_generator,
+            r1cs,
+            private_inputs,
+            start_public_input.clone(),
+            z0_secondary.clone(),
+            &pp,
+            WitnessGeneration::Sequential,
+        )
+        .unwrap();
+
+        (pp, recursive_snark, start_public_input, z0_secondary)
+    }
+
+    fn fold_bn254_2inputs(
+        iteration_count: usize,
+    ) -> (
+        PublicParams<G1, G2, C1<G1>, C2<G2>>,
+        RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>,
+        Vec<F<G1>>,
+        Vec<F<G2>>,
+    ) {
+        fold_2inputs::<G1, G2>(
+            "circuits/2inputs/bn254/bn254.r1cs",
+            "circuits/2inputs/bn254/bn254.wasm",
+            iteration_count,
+        )
+    }
+
+    /// Swaps the trailing `old` bytes of `bytes` for `new`, which must encode to the same length.
+    fn replace_suffix(bytes: &[u8], old: &[u8], new: &[u8]) -> Vec<u8> {
+        assert!(bytes.ends_with(old));
+        assert_eq!(old.len(), new.len());
+
+        let mut bytes = bytes[..bytes.len() - old.len()].to_vec();
+        bytes.extend_from_slice(new);
+        bytes
+    }
+
+    #[test]
+    fn verifiers_reject_wrong_iteration_count() {
+        let iteration_count = 3;
+        let (pp, recursive_snark, z0, z0_secondary) = fold_bn254_2inputs(iteration_count);
+
+        for wrong_count in [iteration_count - 1, iteration_count + 1] {
+            assert!(recursive_snark
+                .verify(&pp, wrong_count, &z0, &z0_secondary)
+                .is_err());
+        }
+
+        for backend in CompressionBackend::ALL {
+            let keys = CompressionKeys::setup(backend, &pp).unwrap();
+            let compressed_snark = keys.prove(&pp, &recursive_snark).unwrap();
+
+            for wrong_count in [iteration_count - 1, iteration_count + 1] {
+                assert!(compressed_snark
+                    .verify(&keys, wrong_count, z0.clone(), z0_secondary.clone())
+                    .is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn verifiers_reject_altered_z0() {
+        let iteration_count = 3;
+        let (pp, recursive_snark, z0, z0_secondary) = fold_bn254_2inputs(iteration_count);
+
+        let mut altered_z0 = z0.clone();
+        altered_z0[1] += F::<G1>::from(1);
+
+        assert!(recursive_snark
+            .verify(&pp, iteration_count, &altered_z0, &z0_secondary)
+            .is_err());
+
+        for backend in CompressionBackend::ALL {
+            let keys = CompressionKeys::setup(backend, &pp).unwrap();
+            let compressed_snark = keys.prove(&pp, &recursive_snark).unwrap();
+
+            assert!(compressed_snark
+                .verify(
+                    &keys,
+                    iteration_count,
+                    altered_z0.clone(),
+                    z0_secondary.clone()
+                )
+                .is_err());
+        }
+    }
+
+    /*
+    The proofs carry the z_n they claim as their last serialized fields (zi_primary, zi_secondary
+    in the RecursiveSNARK, zn_primary, zn_secondary in the CompressedSNARK), so a different claim
+    can be spliced in at the end of the bytes.
+    */
+    #[test]
+    fn verifiers_reject_altered_z_n() {
+        let iteration_count = 3;
+        let (pp, recursive_snark, z0, z0_secondary) = fold_bn254_2inputs(iteration_count);
+        let (z_last, z_last_secondary) = recursive_snark
+            .verify(&pp, iteration_count, &z0, &z0_secondary)
+            .unwrap();
+
+        let mut altered_z_last = z_last.clone();
+        altered_z_last[0] += F::<G1>::from(1);
+
+        let claim = [
+            bincode::serialize(&z_last).unwrap(),
+            bincode::serialize(&z_last_secondary).unwrap(),
+        ]
+        .concat();
+        let altered_claim = [
+            bincode::serialize(&altered_z_last).unwrap(),
+            bincode::serialize(&z_last_secondary).unwrap(),
+        ]
+        .concat();
+
+        let bytes = bincode::serialize(&recursive_snark).unwrap();
+        let altered: RecursiveSNARK<G1, G2, C1<G1>, C2<G2>> =
+            bincode::deserialize(&replace_suffix(&bytes, &claim, &altered_claim)).unwrap();
+        assert!(altered
+            .verify(&pp, iteration_count, &z0, &z0_secondary)
+            .is_err());
+
+        for backend in CompressionBackend::ALL {
+            let keys = CompressionKeys::setup(backend, &pp).unwrap();
+            let bytes = keys.prove(&pp, &recursive_snark).unwrap().to_bytes();
+
+            let altered = CompressedProof::from_bytes(
+                backend,
+                &replace_suffix(&bytes, &claim, &altered_claim),
+            )
+            .unwrap();
+            assert!(altered
+                .verify(&keys, iteration_count, z0.clone(), z0_secondary.clone())
+                .is_err());
+        }
+    }
+
+    /// How tampered proofs were turned down. A proof that decodes and verifies fails the test.
+    #[derive(Debug, Default)]
+    struct Rejections {
+        decoding: usize,
+        verification: usize,
+    }
+
+    impl Rejections {
+        fn check<P, E, T>(
+            &mut self,
+            decoded: Result<P, E>,
+            verify: impl FnOnce(P) -> Result<T, NovaError>,
+            what: &str,
+        ) {
+            match decoded {
+                Err(_) => self.decoding += 1,
+                Ok(proof) => match verify(proof) {
+                    Err(_) => self.verification += 1,
+                    Ok(_) => panic!("{} was accepted", what),
+                },
+            }
+        }
+    }
+
+    /// Every byte of the first 64, where the lengths and first commitments sit, and 64 more spread
+    /// over the rest.
+    fn flip_positions(len: usize) -> Vec<usize> {
+        let header = len.min(64);
+        let mut positions: Vec<usize> = (0..header).collect();
+        positions.extend((header..len).step_by(((len - header) / 64).max(1)));
+        positions
+    }
+
+    #[test]
+    fn verifiers_reject_flipped_bytes() {
+        let iteration_count = 3;
+        let (pp, recursive_snark, z0, z0_secondary) = fold_bn254_2inputs(iteration_count);
+
+        let bytes = bincode::serialize(&recursive_snark).unwrap();
+        let mut rejections = Rejections::default();
+        for position in flip_positions(bytes.len()) {
+            let mut flipped = bytes.clone();
+            flipped[position] ^= 0x01;
+
+            rejections.check(
+                bincode::deserialize::<RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>>(&flipped),
+                |proof| proof.verify(&pp, iteration_count, &z0, &z0_secondary),
+                &format!("RecursiveSNARK with byte {} flipped", position),
+            );
+        }
+        println!("RecursiveSNARK: {:?}", rejections);
+
+        for backend in CompressionBackend::ALL {
+            let keys = CompressionKeys::setup(backend, &pp).unwrap();
+            let bytes = keys.prove(&pp, &recursive_snark).unwrap().to_bytes();
+
+            let mut rejections = Rejections::default();
+            for position in flip_positions(bytes.len()) {
+                let mut flipped = bytes.clone();
+                flipped[position] ^= 0x01;
+
+                rejections.check(
+                    CompressedProof::from_bytes(backend, &flipped),
+                    |proof| proof.verify(&keys, iteration_count, z0.clone(), z0_secondary.clone()),
+                    &format!("{:?} proof with byte {} flipped", backend, position),
+                );
+            }
+            println!("{:?}: {:?}", backend, rejections);
+        }
+    }
+
+    #[test]
+    fn compressed_verifier_rejects_vk_of_other_circuit() {
+        let iteration_count = 3;
+        let (pp, recursive_snark, z0, z0_secondary) = fold_bn254_2inputs(iteration_count);
+
+        let other_r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            "circuits/3inputs/bn254/3inputs.r1cs".into(),
+        ));
+        let other_pp: PublicParams<G1, G2, _, _> = create_public_params(other_r1cs);
+
+        for backend in CompressionBackend::ALL {
+            let keys = CompressionKeys::setup(backend, &pp).unwrap();
+            let other_keys = CompressionKeys::setup(backend, &other_pp).unwrap();
+            let compressed_snark = keys.prove(&pp, &recursive_snark).unwrap();
+
+            assert!(compressed_snark
+                .verify(
+                    &other_keys,
+                    iteration_count,
+                    z0.clone(),
+                    z0_secondary.clone()
+                )
+                .is_err());
+        }
+    }
+
+    /*
+    A pasta proof cannot even be typed as a bn254 one, so it goes through its serialized form, as it
+    would when received over the wire.
+    */
+    #[test]
+    fn bn254_verifiers_reject_pasta_proofs() {
+        type G1Pasta = pasta_curves::vesta::Point;
+        type G2Pasta = pasta_curves::pallas::Point;
+
+        let iteration_count = 3;
+        let (pp, recursive_snark, z0, z0_secondary) = fold_bn254_2inputs(iteration_count);
+        let (pasta_pp, pasta_recursive_snark, _, _) = fold_2inputs::<G1Pasta, G2Pasta>(
+            "circuits/2inputs/pasta/pasta.r1cs",
+            "circuits/2inputs/pasta/pasta.wasm",
+            iteration_count,
+        );
+
+        let bytes = bincode::serialize(&pasta_recursive_snark).unwrap();
+        let mut rejections = Rejections::default();
+        rejections.check(
+            bincode::deserialize::<RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>>(&bytes),
+            |proof| proof.verify(&pp, iteration_count, &z0, &z0_secondary),
+            "pasta RecursiveSNARK",
         );
-    }   
+        println!("RecursiveSNARK: {:?}", rejections);
+
+        for backend in CompressionBackend::ALL {
+            let keys = CompressionKeys::setup(backend, &pp).unwrap();
+            let pasta_keys = CompressionKeys::setup(backend, &pasta_pp).unwrap();
+            let bytes = pasta_keys
+                .prove(&pasta_pp, &pasta_recursive_snark)
+                .unwrap()
+                .to_bytes();
+
+            let mut rejections = Rejections::default();
+            rejections.check(
+                CompressedProof::<G1, G2, C1<G1>, C2<G2>>::from_bytes(backend, &bytes),
+                |proof| proof.verify(&keys, iteration_count, z0.clone(), z0_secondary.clone()),
+                &format!("pasta {:?} proof", backend),
+            );
+            println!("{:?}: {:?}", backend, rejections);
+        }
+
+        // The bn254 proof itself is fine.
+        assert!(recursive_snark
+            .verify(&pp, iteration_count, &z0, &z0_secondary)
+            .is_ok());
+    }
+
+    /*
+    Property tests for the linear adder circuits: random start inputs, a random adder per step and a
+    random number of steps are folded and z_n is compared with the native model. Half of the drawn
+    values sit just below the field modulus, so the sums wrap around it.
+    */
+    struct LinearCircuit {
+        r1cs: R1CS<F<G1>>,
+        witness_generator: WitnessGenerator,
+        pp: PublicParams<G1, G2, C1<G1>, C2<G2>>,
+    }
+
+    fn linear_circuit(circuit_filepath: &str, witness_gen_filepath: &str) -> LinearCircuit {
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(circuit_filepath.into()));
+        let pp = create_public_params(r1cs.clone());
+
+        LinearCircuit {
+            r1cs,
+            witness_generator: WitnessGenerator::from_path(witness_gen_filepath),
+            pp,
+        }
+    }
+
+    fn two_inputs_circuit() -> &'static LinearCircuit {
+        static CIRCUIT: OnceLock<LinearCircuit> = OnceLock::new();
+        CIRCUIT.get_or_init(|| {
+            linear_circuit(
+                "circuits/2inputs/bn254/bn254.r1cs",
+                "circuits/2inputs/bn254/bn254.wasm",
+            )
+        })
+    }
+
+    fn three_inputs_circuit() -> &'static LinearCircuit {
+        static CIRCUIT: OnceLock<LinearCircuit> = OnceLock::new();
+        CIRCUIT.get_or_init(|| {
+            linear_circuit(
+                "circuits/3inputs/bn254/3inputs.r1cs",
+                "circuits/3inputs/bn254/3inputs.wasm",
+            )
+        })
+    }
+
+    fn field_element() -> impl Strategy<Value = F<G1>> {
+        prop_oneof![
+            any::<u64>().prop_map(F::<G1>::from),
+            // p - 1 - k
+            (0u64..1 << 16).prop_map(|k| -F::<G1>::from(k + 1)),
+        ]
+    }
+
+    fn fold_and_compare(
+        circuit: &LinearCircuit,
+        model: &dyn StepModel<F<G1>>,
+        start_public_input: Vec<F<G1>>,
+        adders: Vec<F<G1>>,
+    ) -> Result<(), TestCaseError> {
+        let private_inputs: Vec<_> = adders
+            .iter()
+            .map(|adder| HashMap::from([("adder".to_string(), to_json(adder))]))
+            .collect();
+
+        let mut expected = start_public_input.clone();
+        for private_input in &private_inputs {
+            expected = model.step(&expected, private_input);
+        }
+
+        let z0_secondary = vec![F::<G2>::from(0)];
+        let (recursive_snark, _) = create_recursive_circuit(
+            &circuit.witness_generator,
+            circuit.r1cs.clone(),
+            private_inputs,
+            start_public_input.clone(),
+            z0_secondary.clone(),
+            &circuit.pp,
+            WitnessGeneration::Sequential,
+        )
+        .unwrap();
+
+        let (z_last, _) = recursive_snark
+            .verify(
+                &circuit.pp,
+                adders.len(),
+                &start_public_input,
+                &z0_secondary,
+            )
+            .unwrap();
+        prop_assert_eq!(z_last, expected);
+
+        Ok(())
+    }
+
+    proptest! {
+        // Every case folds a full run, keep the budget small.
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn prop_2inputs_matches_native_model(
+            start_public_input in vec(field_element(), 2),
+            adders in vec(field_element(), 1..=6),
+        ) {
+            fold_and_compare(two_inputs_circuit(), &TwoInputs, start_public_input, adders)?;
+        }
+
+        #[test]
+        fn prop_3inputs_matches_native_model(
+            start_public_input in vec(field_element(), 3),
+            adders in vec(field_element(), 1..=6),
+        ) {
+            fold_and_compare(three_inputs_circuit(), &ThreeInputs, start_public_input, adders)?;
+        }
+    }
 }
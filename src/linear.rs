@@ -0,0 +1,204 @@
+use std::{collections::HashMap, time::Instant};
+
+use ff::PrimeField;
+use nova_scotia::circom::circuit::{CircomCircuit, Constraint, R1CS};
+use serde_json::Value;
+
+use crate::{
+    field,
+    model::{Affine, StepModel},
+    pipeline::StepCircuits,
+};
+
+/*
+A linear step function described in Rust and turned straight into the R1CS circom would produce,
+so circuits like 2inputs and 3inputs need neither circom nor checked-in .r1cs/.wasm files:
+
+    step_out[i] = sum_j coefficients[i][j] * (step_in ++ private inputs)[j]
+
+The variables follow circom's layout, which is what nova-scotia's CircomCircuit expects:
+
+    [1, step_out[0..arity], step_in[0..arity], private inputs...]
+
+and every output is one constraint, (linear combination) * 1 = step_out[i].
+*/
+#[derive(Clone, Debug)]
+pub struct LinearStep<Fr: PrimeField> {
+    pub arity: usize,
+    pub private_inputs: Vec<String>,
+    /// One row per output, over step_in followed by the private inputs.
+    pub coefficients: Vec<Vec<Fr>>,
+}
+
+impl<Fr: PrimeField> LinearStep<Fr> {
+    pub fn new(arity: usize, private_inputs: &[&str]) -> Self {
+        LinearStep {
+            arity,
+            private_inputs: private_inputs.iter().map(|name| name.to_string()).collect(),
+            coefficients: Vec::with_capacity(arity),
+        }
+    }
+
+    /// Adds the next output. Negative coefficients are taken mod p.
+    pub fn output(mut self, coefficients: &[i64]) -> Self {
+        assert_eq!(
+            coefficients.len(),
+            self.arity + self.private_inputs.len(),
+            "an output needs a coefficient for every step_in and private input"
+        );
+        assert!(self.coefficients.len() < self.arity, "too many outputs");
+
+        let to_field = |&c: &i64| {
+            let magnitude = Fr::from(c.unsigned_abs());
+            if c < 0 {
+                -magnitude
+            } else {
+                magnitude
+            }
+        };
+        self.coefficients
+            .push(coefficients.iter().map(to_field).collect());
+        self
+    }
+
+    /*
+        step_out[0] <== step_in[0] + adder;
+        step_out[1] <== step_in[0] + step_in[1];
+    */
+    pub fn two_inputs() -> Self {
+        LinearStep::new(2, &["adder"])
+            .output(&[1, 0, 1])
+            .output(&[1, 1, 0])
+    }
+
+    /*
+        step_out[0] <== step_in[0] + step_in[1] + adder;
+        step_out[1] <== step_in[0] + step_in[1] + step_in[2] + adder;
+        step_out[2] <== step_in[0] + step_in[1] + step_in[2];
+    */
+    pub fn three_inputs() -> Self {
+        LinearStep::new(3, &["adder"])
+            .output(&[1, 1, 0, 1])
+            .output(&[1, 1, 1, 1])
+            .output(&[1, 1, 1, 0])
+    }
+
+    pub fn r1cs(&self) -> R1CS<Fr> {
+        assert_eq!(self.coefficients.len(), self.arity, "missing outputs");
+
+        let step_in_offset = 1 + self.arity;
+        let constraints: Vec<Constraint<Fr>> = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let a = row
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| !bool::from(c.is_zero()))
+                    .map(|(j, c)| (step_in_offset + j, *c))
+                    .collect();
+                (a, vec![(0, Fr::ONE)], vec![(1 + i, Fr::ONE)])
+            })
+            .collect();
+
+        R1CS {
+            num_inputs: 1 + 2 * self.arity,
+            num_aux: self.private_inputs.len(),
+            num_variables: 1 + 2 * self.arity + self.private_inputs.len(),
+            constraints,
+        }
+    }
+
+    /// The full assignment of one step, in the layout of `r1cs`.
+    pub fn witness(&self, step_in: &[Fr], private_input: &HashMap<String, Value>) -> Vec<Fr> {
+        let mut inputs = step_in.to_vec();
+        inputs.extend(
+            self.private_inputs
+                .iter()
+                .map(|name| field::from_json::<Fr>(&private_input[name]).unwrap()),
+        );
+
+        let mut witness = vec![Fr::ONE];
+        witness.extend(self.step(step_in, private_input));
+        witness.extend(inputs);
+        witness
+    }
+
+    /// The circuit of every step, computed natively since there is no witness generator to run.
+    pub fn step_circuits(
+        &self,
+        private_inputs: &[HashMap<String, Value>],
+        start_public_input: &[Fr],
+    ) -> StepCircuits<CircomCircuit<Fr>> {
+        let r1cs = self.r1cs();
+        let mut step_in = start_public_input.to_vec();
+        let mut circuits = Vec::with_capacity(private_inputs.len());
+        let mut witness_times = Vec::with_capacity(private_inputs.len());
+
+        for private_input in private_inputs {
+            let start = Instant::now();
+            let witness = self.witness(&step_in, private_input);
+            witness_times.push(start.elapsed());
+
+            step_in = witness[1..1 + self.arity].to_vec();
+            circuits.push(CircomCircuit {
+                r1cs: r1cs.clone(),
+                witness: Some(witness),
+            });
+        }
+
+        StepCircuits {
+            circuits,
+            witness_times,
+        }
+    }
+
+    /// The step as an affine map of step_in, with the private inputs fixed to `private_input`.
+    pub fn affine(&self, private_input: &HashMap<String, Value>) -> Affine<Fr> {
+        let private: Vec<Fr> = self
+            .private_inputs
+            .iter()
+            .map(|name| field::from_json(&private_input[name]).unwrap())
+            .collect();
+
+        Affine {
+            matrix: self
+                .coefficients
+                .iter()
+                .map(|row| row[..self.arity].to_vec())
+                .collect(),
+            offset: self
+                .coefficients
+                .iter()
+                .map(|row| {
+                    row[self.arity..]
+                        .iter()
+                        .zip(&private)
+                        .map(|(c, x)| *c * x)
+                        .sum()
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<Fr: PrimeField> StepModel<Fr> for LinearStep<Fr> {
+    fn step(&self, step_in: &[Fr], private_input: &HashMap<String, Value>) -> Vec<Fr> {
+        let private: Vec<Fr> = self
+            .private_inputs
+            .iter()
+            .map(|name| field::from_json(&private_input[name]).unwrap())
+            .collect();
+
+        self.coefficients
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(step_in.iter().chain(&private))
+                    .map(|(c, x)| *c * x)
+                    .sum()
+            })
+            .collect()
+    }
+}
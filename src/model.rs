@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use ff::PrimeField;
+use serde_json::Value;
+
+use crate::{
+    composite::component_private_input,
+    field, inputs,
+    poseidon_native::{self, poseidon_hash},
+};
+
+/*
+Native (out of circuit) models of the step functions. Given step_in and the private inputs of a
+step they compute step_out, so the whole sequence of step_in values can be known before any witness
+is generated.
+*/
+pub trait StepModel<Fr: PrimeField> {
+    fn step(&self, step_in: &[Fr], private_input: &HashMap<String, Value>) -> Vec<Fr>;
+}
+
+/// Returns the step_in of every step, starting from `start_public_input`.
+pub fn predict_step_inputs<Fr: PrimeField>(
+    model: &dyn StepModel<Fr>,
+    start_public_input: &[Fr],
+    private_inputs: &[HashMap<String, Value>],
+) -> Vec<Vec<Fr>> {
+    let mut step_inputs = Vec::with_capacity(private_inputs.len());
+    let mut step_in = start_public_input.to_vec();
+
+    for private_input in private_inputs {
+        let step_out = model.step(&step_in, private_input);
+        step_inputs.push(step_in);
+        step_in = step_out;
+    }
+
+    step_inputs
+}
+
+/// Returns step_out of the last step.
+pub fn predict_output<Fr: PrimeField>(
+    model: &dyn StepModel<Fr>,
+    start_public_input: &[Fr],
+    private_inputs: &[HashMap<String, Value>],
+) -> Vec<Fr> {
+    let mut step_in = start_public_input.to_vec();
+    for private_input in private_inputs {
+        step_in = model.step(&step_in, private_input);
+    }
+
+    step_in
+}
+
+fn field_input<Fr: PrimeField>(private_input: &HashMap<String, Value>, name: &str) -> Fr {
+    field::from_json(&private_input[name]).unwrap()
+}
+
+/*
+Closed form of affine steps, step_out = matrix * step_in + offset with the same offset every step
+(the private inputs do not change). With the augmented matrix
+
+    A = | matrix  offset |
+        |   0       1    |
+
+z_n = A^n * (z_0, 1), and A^n takes O(log n) matrix products, so z_n after millions of steps is
+known without replaying them.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Affine<Fr: PrimeField> {
+    pub matrix: Vec<Vec<Fr>>,
+    pub offset: Vec<Fr>,
+}
+
+impl<Fr: PrimeField> Affine<Fr> {
+    /// z_n after `n` steps from `start_public_input`.
+    pub fn output_after(&self, start_public_input: &[Fr], n: u64) -> Vec<Fr> {
+        let d = self.offset.len();
+        assert_eq!(
+            start_public_input.len(),
+            d,
+            "the start public input needs one value per state element"
+        );
+        let mut z = start_public_input.to_vec();
+        z.push(Fr::ONE);
+
+        let power = mat_pow(&self.augmented(), n);
+        power[..d]
+            .iter()
+            .map(|row| row.iter().zip(&z).map(|(a, x)| *a * x).sum())
+            .collect()
+    }
+
+    fn augmented(&self) -> Vec<Vec<Fr>> {
+        let d = self.offset.len();
+        let mut augmented: Vec<Vec<Fr>> = self
+            .matrix
+            .iter()
+            .zip(&self.offset)
+            .map(|(row, offset)| {
+                let mut row = row.clone();
+                row.push(*offset);
+                row
+            })
+            .collect();
+
+        let mut last = vec![Fr::ZERO; d];
+        last.push(Fr::ONE);
+        augmented.push(last);
+        augmented
+    }
+}
+
+impl<Fr: PrimeField> StepModel<Fr> for Affine<Fr> {
+    fn step(&self, step_in: &[Fr], _private_input: &HashMap<String, Value>) -> Vec<Fr> {
+        self.output_after(step_in, 1)
+    }
+}
+
+fn mat_mul<Fr: PrimeField>(a: &[Vec<Fr>], b: &[Vec<Fr>]) -> Vec<Vec<Fr>> {
+    a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|j| row.iter().zip(b).map(|(x, b_row)| *x * b_row[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+fn mat_pow<Fr: PrimeField>(a: &[Vec<Fr>], mut n: u64) -> Vec<Vec<Fr>> {
+    let mut result: Vec<Vec<Fr>> = (0..a.len())
+        .map(|i| {
+            (0..a.len())
+                .map(|j| if i == j { Fr::ONE } else { Fr::ZERO })
+                .collect()
+        })
+        .collect();
+    let mut base = a.to_vec();
+
+    while n > 0 {
+        if n & 1 == 1 {
+            result = mat_mul(&result, &base);
+        }
+        base = mat_mul(&base, &base);
+        n >>= 1;
+    }
+
+    result
+}
+
+/// Model of a `composite::Composite`, with the private inputs prefixed by `first.` and `second.`.
+pub struct Composed<'a, Fr: PrimeField> {
+    pub first: &'a dyn StepModel<Fr>,
+    pub second: &'a dyn StepModel<Fr>,
+}
+
+impl<Fr: PrimeField> StepModel<Fr> for Composed<'_, Fr> {
+    fn step(&self, step_in: &[Fr], private_input: &HashMap<String, Value>) -> Vec<Fr> {
+        let step_out = self
+            .first
+            .step(step_in, &component_private_input(private_input, "first"));
+
+        self.second
+            .step(&step_out, &component_private_input(private_input, "second"))
+    }
+}
+
+/*
+    step_out[0] <== step_in[0] + adder;
+    step_out[1] <== step_in[0] + step_in[1];
+*/
+pub struct TwoInputs;
+
+impl<Fr: PrimeField> StepModel<Fr> for TwoInputs {
+    fn step(&self, step_in: &[Fr], private_input: &HashMap<String, Value>) -> Vec<Fr> {
+        let adder = field_input::<Fr>(private_input, "adder");
+
+        vec![step_in[0] + adder, step_in[0] + step_in[1]]
+    }
+}
+
+impl TwoInputs {
+    pub fn affine<Fr: PrimeField>(private_input: &HashMap<String, Value>) -> Affine<Fr> {
+        let adder = field_input::<Fr>(private_input, "adder");
+        let (zero, one) = (Fr::ZERO, Fr::ONE);
+
+        Affine {
+            matrix: vec![vec![one, zero], vec![one, one]],
+            offset: vec![adder, zero],
+        }
+    }
+}
+
+/*
+    step_out[0] <== step_in[0] + step_in[1] + adder;
+    step_out[1] <== step_in[0] + step_in[1] + step_in[2] + adder;
+    step_out[2] <== step_in[0] + step_in[1] + step_in[2];
+*/
+pub struct ThreeInputs;
+
+impl<Fr: PrimeField> StepModel<Fr> for ThreeInputs {
+    fn step(&self, step_in: &[Fr], private_input: &HashMap<String, Value>) -> Vec<Fr> {
+        let adder = field_input::<Fr>(private_input, "adder");
+        let sum = step_in[0] + step_in[1] + step_in[2];
+
+        vec![step_in[0] + step_in[1] + adder, sum + adder, sum]
+    }
+}
+
+impl ThreeInputs {
+    pub fn affine<Fr: PrimeField>(private_input: &HashMap<String, Value>) -> Affine<Fr> {
+        let adder = field_input::<Fr>(private_input, "adder");
+        let (zero, one) = (Fr::ZERO, Fr::ONE);
+
+        Affine {
+            matrix: vec![
+                vec![one, one, zero],
+                vec![one, one, one],
+                vec![one, one, one],
+            ],
+            offset: vec![adder, adder, zero],
+        }
+    }
+}
+
+/*
+    step_out[0] <== step_in[0] + adders[0] + adders[1] + adders[2];
+    step_out[1] <== step_in[1] + matrix[0][0] + matrix[0][1] + matrix[1][0] + matrix[1][1];
+*/
+pub struct ArrayInputs;
+
+impl<Fr: PrimeField> StepModel<Fr> for ArrayInputs {
+    fn step(&self, step_in: &[Fr], private_input: &HashMap<String, Value>) -> Vec<Fr> {
+        let adders: Fr = inputs::flatten::<Fr>(&private_input["adders"])
+            .unwrap()
+            .into_iter()
+            .sum();
+        let matrix: Fr = inputs::flatten::<Fr>(&private_input["matrix"])
+            .unwrap()
+            .into_iter()
+            .sum();
+
+        vec![step_in[0] + adders, step_in[1] + matrix]
+    }
+}
+
+/*
+The poseidon circuits only exist over BN254, so their models are not generic.
+
+    poseidon_hash:
+        step_out[0] <== Poseidon(2)(step_in[0], step_in[1]);
+        step_out[1] <== Poseidon(2)(step_out[0], Poseidon(1)(priv_hash_input));
+*/
+pub struct PoseidonHash;
+
+impl StepModel<poseidon_native::Fr> for PoseidonHash {
+    fn step(
+        &self,
+        step_in: &[poseidon_native::Fr],
+        private_input: &HashMap<String, Value>,
+    ) -> Vec<poseidon_native::Fr> {
+        let priv_hash_input = field_input::<poseidon_native::Fr>(private_input, "priv_hash_input");
+        let hash_1 = poseidon_hash(&[step_in[0], step_in[1]]);
+        let hash_2 = poseidon_hash(&[priv_hash_input]);
+
+        vec![hash_1, poseidon_hash(&[hash_1, hash_2])]
+    }
+}
+
+/*
+    poseidon_2_priv_inputs:
+        step_out <== Poseidon(2)(Poseidon(2)(step_in, priv_hash_1), priv_hash_2);
+*/
+pub struct Poseidon2PrivInputs;
+
+impl StepModel<poseidon_native::Fr> for Poseidon2PrivInputs {
+    fn step(
+        &self,
+        step_in: &[poseidon_native::Fr],
+        private_input: &HashMap<String, Value>,
+    ) -> Vec<poseidon_native::Fr> {
+        let priv_hash_1 = field_input::<poseidon_native::Fr>(private_input, "priv_hash_1");
+        let priv_hash_2 = field_input::<poseidon_native::Fr>(private_input, "priv_hash_2");
+        let hash_1 = poseidon_hash(&[step_in[0], priv_hash_1]);
+
+        vec![poseidon_hash(&[hash_1, priv_hash_2])]
+    }
+}
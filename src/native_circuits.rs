@@ -0,0 +1,135 @@
+use std::{collections::HashMap, time::Instant};
+
+use bellperson::{gadgets::num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use nova_snark::traits::circuit::StepCircuit;
+use serde_json::Value;
+
+use crate::{field, pipeline::StepCircuits};
+
+/*
+The 2inputs and 3inputs circuits written directly against nova's StepCircuit instead of being
+compiled by circom. They fold through pipeline::fold like the circom ones and must end in the same
+z_n, which makes them a baseline for the overhead of going through nova-scotia's R1CS reader.
+
+Every output is one linear constraint (sum of terms) * 1 = out, and the adder is the only other
+witness. Public params are built with
+`PublicParams::setup(&circuit, &TrivialTestCircuit::default())` on a circuit with a zero adder, the
+shape does not depend on its value.
+*/
+
+#[derive(Clone, Debug, Default)]
+pub struct TwoInputsCircuit<Fr: PrimeField> {
+    pub adder: Fr,
+}
+
+/*
+    step_out[0] <== step_in[0] + adder;
+    step_out[1] <== step_in[0] + step_in[1];
+*/
+impl<Fr: PrimeField> StepCircuit<Fr> for TwoInputsCircuit<Fr> {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let adder = AllocatedNum::alloc(cs.namespace(|| "adder"), || Ok(self.adder))?;
+
+        Ok(vec![
+            sum(cs.namespace(|| "step_out[0]"), &[&z[0], &adder])?,
+            sum(cs.namespace(|| "step_out[1]"), &[&z[0], &z[1]])?,
+        ])
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ThreeInputsCircuit<Fr: PrimeField> {
+    pub adder: Fr,
+}
+
+/*
+    step_out[0] <== step_in[0] + step_in[1] + adder;
+    step_out[1] <== step_in[0] + step_in[1] + step_in[2] + adder;
+    step_out[2] <== step_in[0] + step_in[1] + step_in[2];
+*/
+impl<Fr: PrimeField> StepCircuit<Fr> for ThreeInputsCircuit<Fr> {
+    fn arity(&self) -> usize {
+        3
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let adder = AllocatedNum::alloc(cs.namespace(|| "adder"), || Ok(self.adder))?;
+
+        Ok(vec![
+            sum(cs.namespace(|| "step_out[0]"), &[&z[0], &z[1], &adder])?,
+            sum(
+                cs.namespace(|| "step_out[1]"),
+                &[&z[0], &z[1], &z[2], &adder],
+            )?,
+            sum(cs.namespace(|| "step_out[2]"), &[&z[0], &z[1], &z[2]])?,
+        ])
+    }
+}
+
+impl<Fr: PrimeField> From<Fr> for TwoInputsCircuit<Fr> {
+    fn from(adder: Fr) -> Self {
+        TwoInputsCircuit { adder }
+    }
+}
+
+impl<Fr: PrimeField> From<Fr> for ThreeInputsCircuit<Fr> {
+    fn from(adder: Fr) -> Self {
+        ThreeInputsCircuit { adder }
+    }
+}
+
+/// One circuit per step, built from the `adder` of the same private inputs the circom circuits
+/// take. There is no witness generator to run, the witness times are the time to read the input.
+pub fn adder_step_circuits<Fr: PrimeField, C: From<Fr>>(
+    private_inputs: &[HashMap<String, Value>],
+) -> StepCircuits<C> {
+    let (circuits, witness_times) = private_inputs
+        .iter()
+        .map(|private_input| {
+            let start = Instant::now();
+            let adder: Fr = field::from_json(&private_input["adder"]).unwrap();
+            (C::from(adder), start.elapsed())
+        })
+        .unzip();
+
+    StepCircuits {
+        circuits,
+        witness_times,
+    }
+}
+
+/// Allocates `out = terms[0] + ... + terms[n - 1]` with a single constraint.
+pub(crate) fn sum<Fr: PrimeField, CS: ConstraintSystem<Fr>>(
+    mut cs: CS,
+    terms: &[&AllocatedNum<Fr>],
+) -> Result<AllocatedNum<Fr>, SynthesisError> {
+    let out = AllocatedNum::alloc(cs.namespace(|| "out"), || {
+        terms.iter().try_fold(Fr::ZERO, |acc, term| {
+            term.get_value()
+                .map(|value| acc + value)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })
+    })?;
+
+    cs.enforce(
+        || "out = sum of terms",
+        |lc| terms.iter().fold(lc, |lc, term| lc + term.get_variable()),
+        |lc| lc + CS::one(),
+        |lc| lc + out.get_variable(),
+    );
+
+    Ok(out)
+}
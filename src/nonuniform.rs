@@ -0,0 +1,302 @@
+use std::{collections::HashMap, marker::PhantomData, time::Instant};
+
+use bellperson::{
+    gadgets::{boolean::AllocatedBit, num::AllocatedNum},
+    ConstraintSystem, SynthesisError,
+};
+use ff::{PrimeField, PrimeFieldBits};
+use nova_snark::{provider::poseidon::PoseidonConstantsCircuit, traits::circuit::StepCircuit};
+use serde_json::Value;
+
+use crate::{
+    field,
+    native_circuits::sum,
+    pipeline::StepCircuits,
+    poseidon_circuit::{hash, hash_circuit},
+};
+
+/*
+Non-uniform folding: every step runs one of several instructions sharing the same state, chosen by
+an opcode per step.
+
+nova-snark 0.23 has no SuperNova (it landed in later releases), so this is the classic switch
+construction on top of plain Nova. The folded circuit synthesizes every instruction on the same
+step_in and a one-hot selector picks the outputs of the one named by the opcode:
+
+    step_out[j] = sum_k selector[k] * out_k[j]
+
+so every step pays for all instructions instead of only the one it runs. The opcode of every step
+is folded into an extra last state element, program = program * OPCODES + opcode, which lets the
+verifier check z_n against the program it expects (see `encode_program`).
+*/
+pub trait InstructionSet<Fr: PrimeField>: Clone + Send + Sync {
+    const OPCODES: usize;
+
+    /// Size of the state every instruction works on, without the program element.
+    fn arity(&self) -> usize;
+
+    fn synthesize_instruction<CS: ConstraintSystem<Fr>>(
+        &self,
+        opcode: usize,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError>;
+
+    /// step_out of instruction `opcode`, computed outside of the circuit.
+    fn output(&self, opcode: usize, step_in: &[Fr]) -> Vec<Fr>;
+}
+
+#[derive(Clone)]
+pub struct NonUniformCircuit<Fr: PrimeField, I: InstructionSet<Fr>> {
+    pub opcode: usize,
+    pub instructions: I,
+    _p: PhantomData<Fr>,
+}
+
+impl<Fr: PrimeField, I: InstructionSet<Fr>> NonUniformCircuit<Fr, I> {
+    pub fn new(opcode: usize, instructions: I) -> Self {
+        assert!(opcode < I::OPCODES, "unknown opcode {}", opcode);
+
+        NonUniformCircuit {
+            opcode,
+            instructions,
+            _p: PhantomData,
+        }
+    }
+
+    /// step_out of this step, program element included.
+    pub fn output(&self, step_in: &[Fr]) -> Vec<Fr> {
+        let (program, z) = step_in.split_last().unwrap();
+
+        let mut step_out = self.instructions.output(self.opcode, z);
+        step_out.push(*program * Fr::from(I::OPCODES as u64) + Fr::from(self.opcode as u64));
+        step_out
+    }
+}
+
+impl<Fr: PrimeField, I: InstructionSet<Fr> + Default> Default for NonUniformCircuit<Fr, I> {
+    fn default() -> Self {
+        NonUniformCircuit::new(0, I::default())
+    }
+}
+
+impl<Fr: PrimeField, I: InstructionSet<Fr>> StepCircuit<Fr> for NonUniformCircuit<Fr, I> {
+    fn arity(&self) -> usize {
+        self.instructions.arity() + 1
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let (program, z) = z.split_last().unwrap();
+
+        let selector = (0..I::OPCODES)
+            .map(|opcode| {
+                AllocatedBit::alloc(
+                    cs.namespace(|| format!("selector[{}]", opcode)),
+                    Some(opcode == self.opcode),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        cs.enforce(
+            || "exactly one instruction is selected",
+            |lc| selector.iter().fold(lc, |lc, bit| lc + bit.get_variable()),
+            |lc| lc + CS::one(),
+            |lc| lc + CS::one(),
+        );
+
+        let outputs = (0..I::OPCODES)
+            .map(|opcode| {
+                let mut cs = cs.namespace(|| format!("instruction {}", opcode));
+                self.instructions.synthesize_instruction(opcode, &mut cs, z)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut step_out = Vec::with_capacity(z.len() + 1);
+        for j in 0..z.len() {
+            let mut cs = cs.namespace(|| format!("step_out[{}]", j));
+            let selected = selector
+                .iter()
+                .zip(&outputs)
+                .enumerate()
+                .map(|(opcode, (bit, out))| {
+                    select(cs.namespace(|| format!("opcode {}", opcode)), bit, &out[j])
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            step_out.push(sum(
+                cs.namespace(|| "sum"),
+                &selected.iter().collect::<Vec<_>>(),
+            )?);
+        }
+
+        let opcodes = Fr::from(I::OPCODES as u64);
+        let next_program = AllocatedNum::alloc(cs.namespace(|| "program"), || {
+            let program = program
+                .get_value()
+                .ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(program * opcodes + Fr::from(self.opcode as u64))
+        })?;
+        cs.enforce(
+            || "program = program * OPCODES + opcode",
+            |lc| {
+                selector.iter().enumerate().fold(
+                    lc + (opcodes, program.get_variable()),
+                    |lc, (opcode, bit)| lc + (Fr::from(opcode as u64), bit.get_variable()),
+                )
+            },
+            |lc| lc + CS::one(),
+            |lc| lc + next_program.get_variable(),
+        );
+        step_out.push(next_program);
+
+        Ok(step_out)
+    }
+}
+
+/// The program element of z_n after running `program` from a program element of zero. Only
+/// injective while OPCODES^len(program) stays below the modulus.
+pub fn encode_program<Fr: PrimeField>(opcodes: usize, program: &[usize]) -> Fr {
+    program.iter().fold(Fr::ZERO, |acc, &opcode| {
+        acc * Fr::from(opcodes as u64) + Fr::from(opcode as u64)
+    })
+}
+
+/// One circuit per step of `program`, `instructions[i]` holding the private inputs of step i.
+pub fn program_step_circuits<Fr: PrimeField, I: InstructionSet<Fr>>(
+    program: &[usize],
+    instructions: Vec<I>,
+) -> StepCircuits<NonUniformCircuit<Fr, I>> {
+    assert_eq!(program.len(), instructions.len());
+
+    let (circuits, witness_times) = program
+        .iter()
+        .zip(instructions)
+        .map(|(&opcode, instructions)| {
+            let start = Instant::now();
+            let circuit = NonUniformCircuit::new(opcode, instructions);
+            (circuit, start.elapsed())
+        })
+        .unzip();
+
+    StepCircuits {
+        circuits,
+        witness_times,
+    }
+}
+
+/// `out = bit * x`, with one constraint.
+fn select<Fr: PrimeField, CS: ConstraintSystem<Fr>>(
+    mut cs: CS,
+    bit: &AllocatedBit,
+    x: &AllocatedNum<Fr>,
+) -> Result<AllocatedNum<Fr>, SynthesisError> {
+    let out = AllocatedNum::alloc(cs.namespace(|| "out"), || {
+        let bit = bit.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+        let x = x.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+        Ok(if bit { x } else { Fr::ZERO })
+    })?;
+
+    cs.enforce(
+        || "out = bit * x",
+        |lc| lc + bit.get_variable(),
+        |lc| lc + x.get_variable(),
+        |lc| lc + out.get_variable(),
+    );
+
+    Ok(out)
+}
+
+/*
+An example instruction set over a 3 element state:
+
+    0 (add):  the 3inputs step, with the adder of the step
+    1 (hash): [H(step_in[0], step_in[1], step_in[2]), step_in[0], step_in[1]], with nova's Poseidon
+*/
+pub const ADD: usize = 0;
+pub const HASH: usize = 1;
+
+#[derive(Clone)]
+pub struct AddOrHash<Fr: PrimeField> {
+    pub adder: Fr,
+    constants: PoseidonConstantsCircuit<Fr>,
+}
+
+impl<Fr: PrimeField + PrimeFieldBits> AddOrHash<Fr> {
+    pub fn new(adder: Fr) -> Self {
+        AddOrHash {
+            adder,
+            constants: PoseidonConstantsCircuit::new(),
+        }
+    }
+
+    /// Reads the adder of every step from the private inputs, the hash steps ignore it.
+    pub fn from_private_inputs(private_inputs: &[HashMap<String, Value>]) -> Vec<Self> {
+        let instructions = AddOrHash::default();
+
+        private_inputs
+            .iter()
+            .map(|private_input| AddOrHash {
+                adder: field::from_json(&private_input["adder"]).unwrap(),
+                ..instructions.clone()
+            })
+            .collect()
+    }
+}
+
+impl<Fr: PrimeField + PrimeFieldBits> Default for AddOrHash<Fr> {
+    fn default() -> Self {
+        AddOrHash::new(Fr::ZERO)
+    }
+}
+
+impl<Fr: PrimeField + PrimeFieldBits> InstructionSet<Fr> for AddOrHash<Fr> {
+    const OPCODES: usize = 2;
+
+    fn arity(&self) -> usize {
+        3
+    }
+
+    fn synthesize_instruction<CS: ConstraintSystem<Fr>>(
+        &self,
+        opcode: usize,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        match opcode {
+            ADD => {
+                let adder = AllocatedNum::alloc(cs.namespace(|| "adder"), || Ok(self.adder))?;
+                Ok(vec![
+                    sum(cs.namespace(|| "step_out[0]"), &[&z[0], &z[1], &adder])?,
+                    sum(
+                        cs.namespace(|| "step_out[1]"),
+                        &[&z[0], &z[1], &z[2], &adder],
+                    )?,
+                    sum(cs.namespace(|| "step_out[2]"), &[&z[0], &z[1], &z[2]])?,
+                ])
+            }
+            HASH => {
+                let hash = hash_circuit(
+                    cs.namespace(|| "hash"),
+                    &self.constants,
+                    &[&z[0], &z[1], &z[2]],
+                )?;
+                Ok(vec![hash, z[0].clone(), z[1].clone()])
+            }
+            _ => unreachable!("unknown opcode {}", opcode),
+        }
+    }
+
+    fn output(&self, opcode: usize, z: &[Fr]) -> Vec<Fr> {
+        match opcode {
+            ADD => {
+                let sum = z[0] + z[1] + z[2];
+                vec![z[0] + z[1] + self.adder, sum + self.adder, sum]
+            }
+            HASH => vec![hash(&self.constants, &[z[0], z[1], z[2]]), z[0], z[1]],
+            _ => unreachable!("unknown opcode {}", opcode),
+        }
+    }
+}
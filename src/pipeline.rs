@@ -0,0 +1,297 @@
+use std::{
+    collections::HashMap,
+    env::temp_dir,
+    fs,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use ff::PrimeField;
+use nova_scotia::{
+    circom::circuit::{CircomCircuit, R1CS},
+    C1, C2, F,
+};
+use nova_snark::{
+    errors::NovaError,
+    traits::circuit::{StepCircuit, TrivialTestCircuit},
+    traits::Group,
+    PublicParams, RecursiveSNARK,
+};
+use rayon::prelude::*;
+use serde_json::Value;
+
+use crate::{
+    model::{predict_step_inputs, StepModel},
+    trace::FoldingTrace,
+    witness::{compute_witness, WitnessGenerator},
+};
+
+/*
+How the witnesses of all steps are computed before folding starts.
+
+Sequential: step i needs step_out of step i - 1, which is read back from the witness of step i - 1.
+            This is what nova-scotia's create_recursive_circuit does.
+Parallel:   the native model of the circuit predicts every step_in up front, then all witnesses are
+            generated concurrently on rayon's thread pool.
+*/
+pub enum WitnessGeneration<'a, Fr: PrimeField> {
+    Sequential,
+    Parallel(&'a dyn StepModel<Fr>),
+}
+
+/// The circuit of every step with its witness filled in, plus how long each witness took. `C` is
+/// a `CircomCircuit` for the circom circuits or one of the Rust circuits in native_circuits.rs.
+pub struct StepCircuits<C> {
+    pub circuits: Vec<C>,
+    pub witness_times: Vec<Duration>,
+}
+
+/// Same as nova-scotia's `create_recursive_circuit`, with a choice of how witnesses are generated.
+/// Also returns the per step profile of the run.
+pub fn create_recursive_circuit<G1, G2>(
+    witness_generator: &WitnessGenerator,
+    r1cs: R1CS<F<G1>>,
+    private_inputs: Vec<HashMap<String, Value>>,
+    start_public_input: Vec<F<G1>>,
+    z0_secondary: Vec<F<G2>>,
+    pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
+    witness_generation: WitnessGeneration<F<G1>>,
+) -> Result<(RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>, FoldingTrace), NovaError>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    let step_circuits = generate_circuits(
+        witness_generator,
+        &r1cs,
+        &private_inputs,
+        &start_public_input,
+        witness_generation,
+    );
+
+    fold(pp, &step_circuits, &start_public_input, &z0_secondary)
+}
+
+/// The step circuits [`create_recursive_circuit`] folds, for callers that need more than the
+/// proof, e.g. the trajectory z_0..z_n through `Trajectory::from_circuits`.
+pub fn generate_circuits<Fr: PrimeField>(
+    witness_generator: &WitnessGenerator,
+    r1cs: &R1CS<Fr>,
+    private_inputs: &[HashMap<String, Value>],
+    start_public_input: &[Fr],
+    witness_generation: WitnessGeneration<Fr>,
+) -> StepCircuits<CircomCircuit<Fr>> {
+    match witness_generation {
+        WitnessGeneration::Sequential => generate_circuits_sequential(
+            witness_generator,
+            r1cs,
+            private_inputs,
+            start_public_input,
+        ),
+        WitnessGeneration::Parallel(model) => {
+            let step_inputs = predict_step_inputs(model, start_public_input, private_inputs);
+            generate_circuits_parallel(witness_generator, r1cs, private_inputs, &step_inputs)
+        }
+    }
+}
+
+pub fn generate_circuits_sequential<Fr: PrimeField>(
+    witness_generator: &WitnessGenerator,
+    r1cs: &R1CS<Fr>,
+    private_inputs: &[HashMap<String, Value>],
+    start_public_input: &[Fr],
+) -> StepCircuits<CircomCircuit<Fr>> {
+    let work_dir = create_work_dir();
+    let mut current_public_input = start_public_input.to_vec();
+    let mut circuits = Vec::with_capacity(private_inputs.len());
+    let mut witness_times = Vec::with_capacity(private_inputs.len());
+
+    for (i, private_input) in private_inputs.iter().enumerate() {
+        let start = Instant::now();
+        let witness = compute_witness(
+            witness_generator,
+            &current_public_input,
+            private_input,
+            &work_dir,
+            &format!("step_{}", i),
+        );
+        witness_times.push(start.elapsed());
+
+        let circuit = CircomCircuit {
+            r1cs: r1cs.clone(),
+            witness: Some(witness),
+        };
+        current_public_input = circuit.get_public_outputs();
+        circuits.push(circuit);
+    }
+
+    fs::remove_dir_all(work_dir).unwrap();
+    StepCircuits {
+        circuits,
+        witness_times,
+    }
+}
+
+/// `step_inputs[i]` is the step_in of step i, usually predicted with a `StepModel`.
+pub fn generate_circuits_parallel<Fr: PrimeField>(
+    witness_generator: &WitnessGenerator,
+    r1cs: &R1CS<Fr>,
+    private_inputs: &[HashMap<String, Value>],
+    step_inputs: &[Vec<Fr>],
+) -> StepCircuits<CircomCircuit<Fr>> {
+    assert_eq!(private_inputs.len(), step_inputs.len());
+
+    let work_dir = create_work_dir();
+    let (circuits, witness_times) = private_inputs
+        .par_iter()
+        .zip(step_inputs.par_iter())
+        .enumerate()
+        .map(|(i, (private_input, step_in))| {
+            let start = Instant::now();
+            let witness = compute_witness(
+                witness_generator,
+                step_in,
+                private_input,
+                &work_dir,
+                &format!("step_{}", i),
+            );
+            let circuit = CircomCircuit {
+                r1cs: r1cs.clone(),
+                witness: Some(witness),
+            };
+            (circuit, start.elapsed())
+        })
+        .unzip();
+
+    fs::remove_dir_all(work_dir).unwrap();
+    StepCircuits {
+        circuits,
+        witness_times,
+    }
+}
+
+/// Folds the circuits of all steps, in order, into a RecursiveSNARK, timing every step.
+/// `z0_secondary` is the starting input of the secondary (trivial) circuit, the verifier has to be
+/// given the same value.
+pub fn fold<G1, G2, C>(
+    pp: &PublicParams<G1, G2, C, C2<G2>>,
+    step_circuits: &StepCircuits<C>,
+    start_public_input: &[F<G1>],
+    z0_secondary: &[F<G2>],
+) -> Result<(RecursiveSNARK<G1, G2, C, C2<G2>>, FoldingTrace), NovaError>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    C: StepCircuit<F<G1>>,
+{
+    fold_inspecting(pp, step_circuits, start_public_input, z0_secondary, |_| {})
+}
+
+/// Same as [`fold`], calling `inspect` with the RecursiveSNARK after every step is folded. The
+/// time spent in `inspect` is not part of the trace.
+pub fn fold_inspecting<G1, G2, C>(
+    pp: &PublicParams<G1, G2, C, C2<G2>>,
+    step_circuits: &StepCircuits<C>,
+    start_public_input: &[F<G1>],
+    z0_secondary: &[F<G2>],
+    mut inspect: impl FnMut(&RecursiveSNARK<G1, G2, C, C2<G2>>),
+) -> Result<(RecursiveSNARK<G1, G2, C, C2<G2>>, FoldingTrace), NovaError>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    C: StepCircuit<F<G1>>,
+{
+    let circuit_secondary = TrivialTestCircuit::default();
+
+    let circuits = &step_circuits.circuits;
+    if circuits.is_empty() {
+        return Err(NovaError::InvalidNumSteps);
+    }
+    let mut trace = FoldingTrace::default();
+
+    // RecursiveSNARK::new does the work of step 0, the first prove_step only moves the counter, so
+    // its time is charged to step 0.
+    let start = Instant::now();
+    let mut recursive_snark = RecursiveSNARK::<G1, G2, C, C2<G2>>::new(
+        pp,
+        &circuits[0],
+        &circuit_secondary,
+        start_public_input.to_vec(),
+        z0_secondary.to_vec(),
+    );
+    let mut first_step_time = Some(start.elapsed());
+
+    for (circuit, witness_time) in circuits.iter().zip(&step_circuits.witness_times) {
+        let start = Instant::now();
+        recursive_snark.prove_step(
+            pp,
+            circuit,
+            &circuit_secondary,
+            start_public_input.to_vec(),
+            z0_secondary.to_vec(),
+        )?;
+        let fold_time = start.elapsed() + first_step_time.take().unwrap_or_default();
+        trace.record(*witness_time, fold_time);
+        inspect(&recursive_snark);
+    }
+
+    Ok((recursive_snark, trace))
+}
+
+/// Generates the witnesses of all steps both ways and returns how long each took, (sequential,
+/// parallel). The step_in sequence for the parallel run is read off the sequential one, so this
+/// works for circuits without a native model too.
+pub fn time_witness_generation<Fr: PrimeField>(
+    witness_generator: &WitnessGenerator,
+    r1cs: &R1CS<Fr>,
+    private_inputs: &[HashMap<String, Value>],
+    start_public_input: &[Fr],
+) -> (Duration, Duration) {
+    let start = Instant::now();
+    let sequential =
+        generate_circuits_sequential(witness_generator, r1cs, private_inputs, start_public_input);
+    let sequential_time = start.elapsed();
+
+    let mut step_inputs = vec![start_public_input.to_vec()];
+    step_inputs.extend(
+        sequential
+            .circuits
+            .iter()
+            .map(|circuit| circuit.get_public_outputs()),
+    );
+    // step_out of the last step is no step's input, nor is z_0 when there are no steps.
+    step_inputs.truncate(private_inputs.len());
+
+    let start = Instant::now();
+    let parallel =
+        generate_circuits_parallel(witness_generator, r1cs, private_inputs, &step_inputs);
+    let parallel_time = start.elapsed();
+
+    for (sequential, parallel) in sequential.circuits.iter().zip(&parallel.circuits) {
+        assert_eq!(sequential.witness, parallel.witness);
+    }
+
+    println!(
+        "Witness generation for {} steps: sequential took {:?}, parallel took {:?}",
+        private_inputs.len(),
+        sequential_time,
+        parallel_time
+    );
+
+    (sequential_time, parallel_time)
+}
+
+pub(crate) fn create_work_dir() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let work_dir = temp_dir().join(format!(
+        "nova-scotia-practice-{}-{}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&work_dir).unwrap();
+
+    work_dir
+}
@@ -1,13 +1,25 @@
 use std::{collections::HashMap, env::current_dir, time::Instant};
 
 // Consider nova scotia as some middleware, that will make it easy for you to interact with nova.
-use nova_scotia::{
-    circom::reader::load_r1cs, create_public_params, create_recursive_circuit, FileLocation, F, S,
+use nova_scotia::{circom::reader::load_r1cs, create_public_params, FileLocation, F};
+use nova_snark::{provider, PublicParams};
+
+use crate::{
+    compression::{CompressionBackend, CompressionKeys},
+    field,
+    golden::{self, Golden},
+    model::{predict_output, Poseidon2PrivInputs},
+    pipeline::{create_recursive_circuit, WitnessGeneration},
+    report::{serialized_size, ProofSizes, RunReport},
+    witness::WitnessGenerator,
 };
-use nova_snark::{provider, CompressedSNARK, PublicParams};
-use serde_json::json;
 
-pub fn poseidon_2_priv_inputs(circuit_filepath: String, witness_gen_filepath: String) {
+pub fn poseidon_2_priv_inputs(
+    circuit_filepath: String,
+    witness_gen_filepath: String,
+    z0_secondary: Vec<F<provider::bn256_grumpkin::grumpkin::Point>>,
+    backend: CompressionBackend,
+) -> RunReport<provider::bn256_grumpkin::bn256::Point, provider::bn256_grumpkin::grumpkin::Point> {
     type G1 = provider::bn256_grumpkin::bn256::Point;
     type G2 = provider::bn256_grumpkin::grumpkin::Point;
 
@@ -19,8 +31,8 @@ pub fn poseidon_2_priv_inputs(circuit_filepath: String, witness_gen_filepath: St
 
     let root = current_dir().unwrap();
     let circuit_file = root.join(circuit_filepath);
-    let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(circuit_file));
-    let witness_generator_file = root.join(witness_gen_filepath);
+    let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(circuit_file.clone()));
+    let witness_generator = WitnessGenerator::from_path(root.join(witness_gen_filepath));
 
     let iteration_count = 1;
     let priv_hash_1 = 77;
@@ -29,8 +41,14 @@ pub fn poseidon_2_priv_inputs(circuit_filepath: String, witness_gen_filepath: St
 
     for _ in 0..iteration_count {
         let mut private_input = HashMap::new();
-        private_input.insert("priv_hash_1".to_string(), json!(priv_hash_1));
-        private_input.insert("priv_hash_2".to_string(), json!(priv_hash_2));
+        private_input.insert(
+            "priv_hash_1".to_string(),
+            field::to_json(&F::<G1>::from(priv_hash_1)),
+        );
+        private_input.insert(
+            "priv_hash_2".to_string(),
+            field::to_json(&F::<G1>::from(priv_hash_2)),
+        );
         private_inputs.push(private_input);
     }
 
@@ -60,32 +78,53 @@ pub fn poseidon_2_priv_inputs(circuit_filepath: String, witness_gen_filepath: St
 
     println!("Creating a RecursiveSNARK...");
     let start = Instant::now();
-    let recursive_snark = create_recursive_circuit(
-        FileLocation::PathBuf(witness_generator_file.clone()),
+    let (recursive_snark, trace) = create_recursive_circuit(
+        &witness_generator,
         r1cs.clone(),
-        private_inputs,
+        private_inputs.clone(),
         start_public_input.to_vec(),
+        z0_secondary.clone(),
         &pp,
+        WitnessGeneration::Sequential,
     )
     .unwrap();
     println!("RecursiveSNARK creation took {:?}", start.elapsed());
-
-    let z0_secondary = [F::<G2>::from(0)];
+    println!("Per step profile:\n{}", trace.to_csv());
 
     println!("Verifying a RecursiveSNARK...");
     let start = Instant::now();
     let res = recursive_snark.verify(&pp, iteration_count, &start_public_input, &z0_secondary);
     println!(
         "RecursiveSNARK::verify: {:?}, took {:?}",
-        res,
+        res.is_ok(),
         start.elapsed()
     );
     assert!(res.is_ok());
 
-    println!("Generating a CompressedSNARK using Spartan with IPA-PC...");
+    let (z_last, z_last_secondary) = res.unwrap();
+    println!(
+        "z_n: {}, z_n of the secondary circuit: {}",
+        field::to_decimal_list(&z_last),
+        field::to_decimal_list(&z_last_secondary)
+    );
+    assert_eq!(
+        z_last,
+        predict_output(&Poseidon2PrivInputs, &start_public_input, &private_inputs)
+    );
+    golden::check(
+        &Golden::path_for(&circuit_file),
+        &start_public_input,
+        &private_inputs,
+        &z_last,
+    );
+
+    println!(
+        "Generating a CompressedSNARK using {:?} with IPA-PC...",
+        backend
+    );
     let start = Instant::now();
-    let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
-    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(&pp, &pk, &recursive_snark);
+    let keys = CompressionKeys::setup(backend, &pp).unwrap();
+    let res = keys.prove(&pp, &recursive_snark);
     println!(
         "CompressedSNARK::prove: {:?}, took {:?}",
         res.is_ok(),
@@ -97,7 +136,7 @@ pub fn poseidon_2_priv_inputs(circuit_filepath: String, witness_gen_filepath: St
     println!("Verifying a CompressedSNARK...");
     let start = Instant::now();
     let res = compressed_snark.verify(
-        &vk,
+        &keys,
         iteration_count,
         start_public_input.to_vec(),
         z0_secondary.to_vec(),
@@ -109,12 +148,30 @@ pub fn poseidon_2_priv_inputs(circuit_filepath: String, witness_gen_filepath: St
     );
     assert!(res.is_ok());
 
+    let sizes = ProofSizes {
+        public_params: serialized_size(&pp),
+        recursive_snark: serialized_size(&recursive_snark),
+        compressed_snark: compressed_snark.serialized_size(),
+        prover_key: keys.prover_key_size(),
+        verifier_key: keys.verifier_key_size(),
+    };
+    println!("{}", sizes);
+
     /*
-    You can check results from https://zkrepl.dev/
-    Ensure that you get the following output in your terminal
-    RecursiveSNARK::verify: Ok(([
-        0x036435f0a0702c00f80a5102a599a6081ca80cf615381a3f7cb20cff4e82b121],
-       [0x0000000000000000000000000000000000000000000000000000000000000000]
-    ))
+    You can check results from https://zkrepl.dev/, z_last is also checked above against the native
+    Poseidon in poseidon_native.rs and against
+    circuits/poseidon_2_priv_inputs/poseidon_2_priv_inputs.golden.json. Ensure that you get the
+    following output in your terminal
+    RecursiveSNARK::verify: true, took ...
+    z_n: [a], z_n of the secondary circuit: [0]
+    where
+    a = 1533995532067329120042608454643775344997771546183319674071351395703345819937
     */
+
+    RunReport {
+        z_last,
+        z_last_secondary,
+        trace,
+        sizes,
+    }
 }
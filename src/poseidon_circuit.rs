@@ -0,0 +1,168 @@
+use std::{collections::HashMap, time::Instant};
+
+use bellperson::{
+    gadgets::{boolean::AllocatedBit, num::AllocatedNum},
+    ConstraintSystem, SynthesisError,
+};
+use ff::{PrimeField, PrimeFieldBits};
+use nova_snark::{
+    provider::poseidon::{PoseidonConstantsCircuit, PoseidonRO, PoseidonROCircuit},
+    traits::{circuit::StepCircuit, ROCircuitTrait, ROConstantsTrait, ROTrait},
+};
+use serde_json::Value;
+
+use crate::{field, pipeline::StepCircuits};
+
+/*
+Same chaining as circuits/poseidon/poseidon_hash.circom, with nova's own Poseidon gadget (the one
+it uses for its random oracle) instead of circomlib's:
+
+    step_out[0] = H(step_in[0], step_in[1])
+    step_out[1] = H(step_out[0], H(priv_hash_input))
+
+nova's Poseidon is neptune's with a width of 25 and outputs are truncated to NUM_HASH_BITS bits, so
+the hashes are not circomlib's and z_n differs from the circom circuit. Only the shape, and so the
+constraint count, is comparable. `output` computes the same hashes natively.
+*/
+
+/// What nova truncates its random oracle outputs to, small enough to fit in either field.
+pub const NUM_HASH_BITS: usize = 250;
+
+#[derive(Clone)]
+pub struct PoseidonCircuit<Fr: PrimeField> {
+    pub priv_hash_input: Fr,
+    constants: PoseidonConstantsCircuit<Fr>,
+}
+
+impl<Fr: PrimeField + PrimeFieldBits> PoseidonCircuit<Fr> {
+    pub fn new(priv_hash_input: Fr) -> Self {
+        PoseidonCircuit {
+            priv_hash_input,
+            constants: PoseidonConstantsCircuit::new(),
+        }
+    }
+
+    /// step_out of this step, computed outside of the circuit.
+    pub fn output(&self, step_in: &[Fr]) -> Vec<Fr> {
+        let constants = &self.constants;
+        let hash_1 = hash(constants, &[step_in[0], step_in[1]]);
+        let hash_2 = hash(constants, &[self.priv_hash_input]);
+
+        vec![hash_1, hash(constants, &[hash_1, hash_2])]
+    }
+}
+
+impl<Fr: PrimeField + PrimeFieldBits> Default for PoseidonCircuit<Fr> {
+    fn default() -> Self {
+        PoseidonCircuit::new(Fr::ZERO)
+    }
+}
+
+impl<Fr: PrimeField + PrimeFieldBits> StepCircuit<Fr> for PoseidonCircuit<Fr> {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let priv_hash_input = AllocatedNum::alloc(cs.namespace(|| "priv_hash_input"), || {
+            Ok(self.priv_hash_input)
+        })?;
+
+        let constants = &self.constants;
+        let hash_1 = hash_circuit(cs.namespace(|| "hash_1"), constants, &[&z[0], &z[1]])?;
+        let hash_2 = hash_circuit(cs.namespace(|| "hash_2"), constants, &[&priv_hash_input])?;
+        let hash_3 = hash_circuit(cs.namespace(|| "hash_3"), constants, &[&hash_1, &hash_2])?;
+
+        Ok(vec![hash_1, hash_3])
+    }
+}
+
+/// One circuit per step from the `priv_hash_input` of each private input, sharing the constants.
+pub fn poseidon_step_circuits<Fr: PrimeField + PrimeFieldBits>(
+    private_inputs: &[HashMap<String, Value>],
+) -> StepCircuits<PoseidonCircuit<Fr>> {
+    let circuit = PoseidonCircuit::default();
+    let (circuits, witness_times) = private_inputs
+        .iter()
+        .map(|private_input| {
+            let start = Instant::now();
+            let priv_hash_input = field::from_json(&private_input["priv_hash_input"]).unwrap();
+            let circuit = PoseidonCircuit {
+                priv_hash_input,
+                ..circuit.clone()
+            };
+            (circuit, start.elapsed())
+        })
+        .unzip();
+
+    StepCircuits {
+        circuits,
+        witness_times,
+    }
+}
+
+/// nova's Poseidon of `inputs`, truncated to NUM_HASH_BITS bits.
+pub fn hash<Fr: PrimeField + PrimeFieldBits>(
+    constants: &PoseidonConstantsCircuit<Fr>,
+    inputs: &[Fr],
+) -> Fr {
+    let mut ro = PoseidonRO::<Fr, Fr>::new(constants.clone(), inputs.len());
+    for input in inputs {
+        ro.absorb(*input);
+    }
+
+    ro.squeeze(NUM_HASH_BITS)
+}
+
+/// The in-circuit version of [`hash`].
+pub fn hash_circuit<Fr: PrimeField + PrimeFieldBits, CS: ConstraintSystem<Fr>>(
+    mut cs: CS,
+    constants: &PoseidonConstantsCircuit<Fr>,
+    inputs: &[&AllocatedNum<Fr>],
+) -> Result<AllocatedNum<Fr>, SynthesisError> {
+    let mut ro = PoseidonROCircuit::new(constants.clone(), inputs.len());
+    for input in inputs {
+        ro.absorb(input);
+    }
+    let bits = ro.squeeze(cs.namespace(|| "squeeze"), NUM_HASH_BITS)?;
+
+    bits_to_num(cs.namespace(|| "bits to num"), &bits)
+}
+
+/// Packs little endian bits into a single number, with one constraint.
+fn bits_to_num<Fr: PrimeField, CS: ConstraintSystem<Fr>>(
+    mut cs: CS,
+    bits: &[AllocatedBit],
+) -> Result<AllocatedNum<Fr>, SynthesisError> {
+    let num = AllocatedNum::alloc(cs.namespace(|| "num"), || {
+        let mut num = Fr::ZERO;
+        let mut coeff = Fr::ONE;
+        for bit in bits {
+            if bit.get_value().ok_or(SynthesisError::AssignmentMissing)? {
+                num += coeff;
+            }
+            coeff = coeff.double();
+        }
+        Ok(num)
+    })?;
+
+    cs.enforce(
+        || "num = sum of bits",
+        |mut lc| {
+            let mut coeff = Fr::ONE;
+            for bit in bits {
+                lc = lc + (coeff, bit.get_variable());
+                coeff = coeff.double();
+            }
+            lc
+        },
+        |lc| lc + CS::one(),
+        |lc| lc + num.get_variable(),
+    );
+
+    Ok(num)
+}
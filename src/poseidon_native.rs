@@ -0,0 +1,130 @@
+use std::sync::OnceLock;
+
+use ff::{Field, PrimeField};
+use nova_scotia::F;
+use nova_snark::provider;
+
+use crate::poseidon_constants::{ARK_T2, ARK_T3, MDS_T2, MDS_T3};
+
+/*
+Native version of circomlib's Poseidon(1) and Poseidon(2), so the outputs of the poseidon circuits
+can be computed in Rust instead of being copied from zkrepl.dev.
+
+circomlib's poseidon.circom is the optimized form of the same permutation (it precomputes sparse
+matrices out of the constants below), the results are identical:
+
+    state = [0, inputs...]
+    4 full rounds, then the partial rounds, then 4 full rounds:
+        add the round constants, x^5 on every element (full) or only on state[0] (partial), MDS
+    out = state[0]
+*/
+pub type Fr = F<provider::bn256_grumpkin::bn256::Point>;
+
+const FULL_ROUNDS: usize = 8;
+
+struct Parameters {
+    t: usize,
+    partial_rounds: usize,
+    ark: Vec<Fr>,
+    mds: Vec<Vec<Fr>>,
+}
+
+impl Parameters {
+    fn new<const T: usize>(partial_rounds: usize, ark: &[&str], mds: &[[&str; T]; T]) -> Self {
+        let parse = |x: &&str| Fr::from_str_vartime(x).unwrap();
+
+        Parameters {
+            t: T,
+            partial_rounds,
+            ark: ark.iter().map(parse).collect(),
+            mds: mds
+                .iter()
+                .map(|row| row.iter().map(parse).collect())
+                .collect(),
+        }
+    }
+}
+
+fn parameters(t: usize) -> &'static Parameters {
+    static T2: OnceLock<Parameters> = OnceLock::new();
+    static T3: OnceLock<Parameters> = OnceLock::new();
+
+    match t {
+        2 => T2.get_or_init(|| Parameters::new(56, &ARK_T2, &MDS_T2)),
+        3 => T3.get_or_init(|| Parameters::new(57, &ARK_T3, &MDS_T3)),
+        _ => panic!("only Poseidon(1) and Poseidon(2) are supported"),
+    }
+}
+
+/// circomlib's `Poseidon(inputs.len())`, for one or two inputs.
+pub fn poseidon_hash(inputs: &[Fr]) -> Fr {
+    let params = parameters(inputs.len() + 1);
+
+    let mut state = vec![Fr::ZERO];
+    state.extend_from_slice(inputs);
+
+    let half_full_rounds = FULL_ROUNDS / 2;
+    for round in 0..FULL_ROUNDS + params.partial_rounds {
+        for (i, x) in state.iter_mut().enumerate() {
+            *x += params.ark[round * params.t + i];
+        }
+
+        let is_full_round =
+            round < half_full_rounds || round >= half_full_rounds + params.partial_rounds;
+        if is_full_round {
+            for x in state.iter_mut() {
+                *x = x.pow_vartime([5]);
+            }
+        } else {
+            state[0] = state[0].pow_vartime([5]);
+        }
+
+        state = params
+            .mds
+            .iter()
+            .map(|row| row.iter().zip(&state).map(|(m, x)| *m * x).sum())
+            .collect();
+    }
+
+    state[0]
+}
+
+#[cfg(test)]
+mod test {
+    use super::{poseidon_hash, Fr};
+    use crate::field;
+
+    fn from_hex(hex: &str) -> Fr {
+        field::from_str(&format!("0x{}", hex)).unwrap()
+    }
+
+    #[test]
+    fn circomlib_test_vectors() {
+        assert_eq!(
+            poseidon_hash(&[Fr::from(1)]),
+            from_hex("29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133")
+        );
+        assert_eq!(
+            poseidon_hash(&[Fr::from(1), Fr::from(2)]),
+            from_hex("115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a")
+        );
+    }
+
+    // The outputs of the poseidon circuits computed on zkrepl.dev.
+    #[test]
+    fn zkrepl_outputs() {
+        let hash_1 = poseidon_hash(&[Fr::from(5), Fr::from(77)]);
+        assert_eq!(
+            hash_1,
+            from_hex("0d488bfc9bfd9fedd864dc2c2af3b933a154f7f49e755c88a95726e5f5772728")
+        );
+        assert_eq!(
+            poseidon_hash(&[hash_1, poseidon_hash(&[Fr::from(9)])]),
+            from_hex("2866bbedf6286dec83eafaa4d538843e3cf170389d1732875bb943fb25081fab")
+        );
+        assert_eq!(
+            poseidon_hash(&[hash_1, Fr::from(9)]),
+            from_hex("036435f0a0702c00f80a5102a599a6081ca80cf615381a3f7cb20cff4e82b121")
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use std::fmt;
+
+use ff::PrimeField;
+use nova_scotia::circom::circuit::R1CS;
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::field;
+
+/*
+A reference implementation of Nova's non-interactive folding scheme (NIFS) for relaxed R1CS, on the
+R1CS circom produces and the witnesses of its witness generators. It only exists to be read next to
+the paper and to cross-check what nova-snark does, nothing in it is zero knowledge or sound.
+
+A relaxed R1CS instance U = (comm_W, comm_E, u, X) with witness (W, E) is satisfied when
+
+    Az ∘ Bz = u·Cz + E,    z = (u, X, W)
+
+which lines up with circom's witness layout [1, public signals..., private signals...] with the
+constant one replaced by u. A circom witness is the fresh instance u = 1, E = 0. Folding (U1, W1)
+and (U2, W2) with the cross term
+
+    T = Az1 ∘ Bz2 + Az2 ∘ Bz1 - u1·Cz2 - u2·Cz1
+
+and a challenge r hashed from both instances and comm_T gives
+
+    u = u1 + r·u2,  X = X1 + r·X2,  W = W1 + r·W2,  E = E1 + r·T + r²·E2
+
+with the commitments combined the same way, which is all the verifier computes. As in Nova, the
+challenge is 128 bits and a run starts from the all zero instance, every step being folded into it.
+With linear constraints only, as in 2inputs and 3inputs, T and so E stay zero, it takes a
+non-linear circuit like poseidon_hash to see the error term grow.
+
+The commitments are a Pedersen-like inner product with generators in Fr itself. They are additively
+homomorphic, which is what folding needs, but not binding: use them to check the algebra only.
+*/
+
+#[derive(Clone, Debug)]
+pub struct CommitmentKey<Fr: PrimeField> {
+    pub generators: Vec<Fr>,
+}
+
+impl<Fr: PrimeField> CommitmentKey<Fr> {
+    /// `n` generators, the i-th being sha256(label || i) taken mod p.
+    pub fn new(label: &str, n: usize) -> Self {
+        let generators = (0..n as u64)
+            .map(|i| {
+                let hash = Sha256::new()
+                    .chain_update(label.as_bytes())
+                    .chain_update(i.to_le_bytes())
+                    .finalize();
+                field::from_biguint(&BigUint::from_bytes_le(&hash))
+            })
+            .collect();
+
+        CommitmentKey { generators }
+    }
+
+    /// Enough generators for both W and E of `r1cs`.
+    pub fn for_r1cs(r1cs: &R1CS<Fr>) -> Self {
+        CommitmentKey::new("relaxed_r1cs", r1cs.num_aux.max(r1cs.constraints.len()))
+    }
+
+    pub fn commit(&self, v: &[Fr]) -> Fr {
+        assert!(v.len() <= self.generators.len(), "not enough generators");
+
+        self.generators.iter().zip(v).map(|(g, x)| *g * x).sum()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedR1CSInstance<Fr: PrimeField> {
+    pub comm_w: Fr,
+    pub comm_e: Fr,
+    pub u: Fr,
+    /// The public signals, step_out followed by step_in for the step circuits.
+    pub x: Vec<Fr>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedR1CSWitness<Fr: PrimeField> {
+    pub w: Vec<Fr>,
+    pub e: Vec<Fr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unsatisfied {
+    /// Az ∘ Bz = u·Cz + E does not hold at this constraint.
+    Constraint(usize),
+    CommitmentToW,
+    CommitmentToE,
+}
+
+impl fmt::Display for Unsatisfied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unsatisfied::Constraint(i) => write!(f, "constraint {} is not satisfied", i),
+            Unsatisfied::CommitmentToW => write!(f, "comm_W is not a commitment to W"),
+            Unsatisfied::CommitmentToE => write!(f, "comm_E is not a commitment to E"),
+        }
+    }
+}
+
+impl std::error::Error for Unsatisfied {}
+
+impl<Fr: PrimeField> RelaxedR1CSInstance<Fr> {
+    /// The all zero instance, satisfied by the all zero witness.
+    pub fn zero(r1cs: &R1CS<Fr>) -> Self {
+        RelaxedR1CSInstance {
+            comm_w: Fr::ZERO,
+            comm_e: Fr::ZERO,
+            u: Fr::ZERO,
+            x: vec![Fr::ZERO; r1cs.num_inputs - 1],
+        }
+    }
+
+    /// The verifier's half of folding: the folded instance, from the two instances and comm_T.
+    pub fn fold(&self, other: &Self, comm_t: Fr) -> Self {
+        let r = challenge(self, other, comm_t);
+
+        RelaxedR1CSInstance {
+            comm_w: self.comm_w + r * other.comm_w,
+            comm_e: self.comm_e + r * comm_t + r * r * other.comm_e,
+            u: self.u + r * other.u,
+            x: combine(&self.x, r, &other.x),
+        }
+    }
+}
+
+impl<Fr: PrimeField> RelaxedR1CSWitness<Fr> {
+    pub fn zero(r1cs: &R1CS<Fr>) -> Self {
+        RelaxedR1CSWitness {
+            w: vec![Fr::ZERO; r1cs.num_aux],
+            e: vec![Fr::ZERO; r1cs.constraints.len()],
+        }
+    }
+
+    /// Splits a full circom witness into a fresh instance (u = 1, E = 0) and its witness.
+    pub fn from_circom(
+        ck: &CommitmentKey<Fr>,
+        r1cs: &R1CS<Fr>,
+        witness: &[Fr],
+    ) -> (RelaxedR1CSInstance<Fr>, Self) {
+        assert_eq!(witness.len(), r1cs.num_variables);
+        assert_eq!(
+            witness[0],
+            Fr::ONE,
+            "a circom witness starts with the constant one"
+        );
+
+        let w = witness[r1cs.num_inputs..].to_vec();
+        let instance = RelaxedR1CSInstance {
+            comm_w: ck.commit(&w),
+            comm_e: Fr::ZERO,
+            u: Fr::ONE,
+            x: witness[1..r1cs.num_inputs].to_vec(),
+        };
+
+        let witness = RelaxedR1CSWitness {
+            w,
+            e: vec![Fr::ZERO; r1cs.constraints.len()],
+        };
+        (instance, witness)
+    }
+}
+
+/// z = (u, X, W), in the order of the R1CS variables.
+fn z<Fr: PrimeField>(
+    instance: &RelaxedR1CSInstance<Fr>,
+    witness: &RelaxedR1CSWitness<Fr>,
+) -> Vec<Fr> {
+    let mut z = Vec::with_capacity(1 + instance.x.len() + witness.w.len());
+    z.push(instance.u);
+    z.extend(&instance.x);
+    z.extend(&witness.w);
+    z
+}
+
+/// (Az, Bz, Cz), one entry per constraint.
+fn multiply<Fr: PrimeField>(r1cs: &R1CS<Fr>, z: &[Fr]) -> (Vec<Fr>, Vec<Fr>, Vec<Fr>) {
+    assert_eq!(z.len(), r1cs.num_variables);
+
+    let eval = |lc: &[(usize, Fr)]| -> Fr { lc.iter().map(|(i, coeff)| z[*i] * coeff).sum() };
+    let mut az = Vec::with_capacity(r1cs.constraints.len());
+    let mut bz = Vec::with_capacity(r1cs.constraints.len());
+    let mut cz = Vec::with_capacity(r1cs.constraints.len());
+    for (a, b, c) in &r1cs.constraints {
+        az.push(eval(a));
+        bz.push(eval(b));
+        cz.push(eval(c));
+    }
+
+    (az, bz, cz)
+}
+
+/// `a + r·b`, entry by entry.
+fn combine<Fr: PrimeField>(a: &[Fr], r: Fr, b: &[Fr]) -> Vec<Fr> {
+    assert_eq!(a.len(), b.len());
+
+    a.iter().zip(b).map(|(a, b)| *a + r * b).collect()
+}
+
+pub fn cross_term<Fr: PrimeField>(
+    r1cs: &R1CS<Fr>,
+    (u1, w1): (&RelaxedR1CSInstance<Fr>, &RelaxedR1CSWitness<Fr>),
+    (u2, w2): (&RelaxedR1CSInstance<Fr>, &RelaxedR1CSWitness<Fr>),
+) -> Vec<Fr> {
+    let (az1, bz1, cz1) = multiply(r1cs, &z(u1, w1));
+    let (az2, bz2, cz2) = multiply(r1cs, &z(u2, w2));
+
+    (0..r1cs.constraints.len())
+        .map(|i| az1[i] * bz2[i] + az2[i] * bz1[i] - u1.u * cz2[i] - u2.u * cz1[i])
+        .collect()
+}
+
+/// The Fiat-Shamir challenge: the first 128 bits of the sha256 of both instances and comm_T.
+pub fn challenge<Fr: PrimeField>(
+    u1: &RelaxedR1CSInstance<Fr>,
+    u2: &RelaxedR1CSInstance<Fr>,
+    comm_t: Fr,
+) -> Fr {
+    let mut hasher = Sha256::new();
+    for instance in [u1, u2] {
+        for x in [instance.comm_w, instance.comm_e, instance.u]
+            .iter()
+            .chain(&instance.x)
+        {
+            hasher.update(x.to_repr().as_ref());
+        }
+    }
+    hasher.update(comm_t.to_repr().as_ref());
+
+    field::from_biguint(&BigUint::from_bytes_le(&hasher.finalize()[..16]))
+}
+
+/// The prover's half of folding: the folded instance and witness, and comm_T for the verifier.
+pub fn fold<Fr: PrimeField>(
+    ck: &CommitmentKey<Fr>,
+    r1cs: &R1CS<Fr>,
+    (u1, w1): (&RelaxedR1CSInstance<Fr>, &RelaxedR1CSWitness<Fr>),
+    (u2, w2): (&RelaxedR1CSInstance<Fr>, &RelaxedR1CSWitness<Fr>),
+) -> (RelaxedR1CSInstance<Fr>, RelaxedR1CSWitness<Fr>, Fr) {
+    let t = cross_term(r1cs, (u1, w1), (u2, w2));
+    let comm_t = ck.commit(&t);
+
+    let instance = u1.fold(u2, comm_t);
+    let r = challenge(u1, u2, comm_t);
+    let witness = RelaxedR1CSWitness {
+        w: combine(&w1.w, r, &w2.w),
+        e: combine(&combine(&w1.e, r, &t), r * r, &w2.e),
+    };
+
+    (instance, witness, comm_t)
+}
+
+/// Folds the circom witness of every step, in order, into the zero instance.
+pub fn fold_witnesses<Fr: PrimeField>(
+    ck: &CommitmentKey<Fr>,
+    r1cs: &R1CS<Fr>,
+    witnesses: &[Vec<Fr>],
+) -> (RelaxedR1CSInstance<Fr>, RelaxedR1CSWitness<Fr>) {
+    let mut running = (
+        RelaxedR1CSInstance::zero(r1cs),
+        RelaxedR1CSWitness::zero(r1cs),
+    );
+    for witness in witnesses {
+        let (instance, witness) = RelaxedR1CSWitness::from_circom(ck, r1cs, witness);
+        let (folded_instance, folded_witness, _) =
+            fold(ck, r1cs, (&running.0, &running.1), (&instance, &witness));
+        running = (folded_instance, folded_witness);
+    }
+
+    running
+}
+
+pub fn is_satisfied<Fr: PrimeField>(
+    ck: &CommitmentKey<Fr>,
+    r1cs: &R1CS<Fr>,
+    instance: &RelaxedR1CSInstance<Fr>,
+    witness: &RelaxedR1CSWitness<Fr>,
+) -> Result<(), Unsatisfied> {
+    assert_eq!(witness.e.len(), r1cs.constraints.len());
+
+    let (az, bz, cz) = multiply(r1cs, &z(instance, witness));
+    if let Some(i) =
+        (0..r1cs.constraints.len()).find(|&i| az[i] * bz[i] != instance.u * cz[i] + witness.e[i])
+    {
+        return Err(Unsatisfied::Constraint(i));
+    }
+    if ck.commit(&witness.w) != instance.comm_w {
+        return Err(Unsatisfied::CommitmentToW);
+    }
+    if ck.commit(&witness.e) != instance.comm_e {
+        return Err(Unsatisfied::CommitmentToE);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{linear::LinearStep, poseidon_native::Fr};
+
+    fn folded_three_inputs(
+        iteration_count: usize,
+    ) -> (
+        CommitmentKey<Fr>,
+        R1CS<Fr>,
+        RelaxedR1CSInstance<Fr>,
+        RelaxedR1CSWitness<Fr>,
+    ) {
+        let step = LinearStep::<Fr>::three_inputs();
+        let private_inputs =
+            vec![HashMap::from([("adder".to_string(), json!(9))]); iteration_count];
+        let start = [Fr::from(4), Fr::from(7), Fr::from(8)];
+        let witnesses: Vec<Vec<Fr>> = step
+            .step_circuits(&private_inputs, &start)
+            .circuits
+            .into_iter()
+            .map(|circuit| circuit.witness.unwrap())
+            .collect();
+
+        let r1cs = step.r1cs();
+        let ck = CommitmentKey::for_r1cs(&r1cs);
+        let (instance, witness) = fold_witnesses(&ck, &r1cs, &witnesses);
+        (ck, r1cs, instance, witness)
+    }
+
+    #[test]
+    fn folded_instance_is_satisfied() {
+        let (ck, r1cs, instance, witness) = folded_three_inputs(5);
+
+        assert_eq!(is_satisfied(&ck, &r1cs, &instance, &witness), Ok(()));
+        assert_ne!(instance.u, Fr::ONE);
+    }
+
+    #[test]
+    fn tampered_witness_is_rejected() {
+        let (ck, r1cs, instance, witness) = folded_three_inputs(3);
+
+        // The adder, used by the first two constraints.
+        let mut tampered = witness.clone();
+        tampered.w[0] += Fr::ONE;
+        assert_eq!(
+            is_satisfied(&ck, &r1cs, &instance, &tampered),
+            Err(Unsatisfied::Constraint(0))
+        );
+
+        let mut tampered = witness.clone();
+        tampered.e[1] += Fr::ONE;
+        assert_eq!(
+            is_satisfied(&ck, &r1cs, &instance, &tampered),
+            Err(Unsatisfied::Constraint(1))
+        );
+
+        let tampered = RelaxedR1CSInstance {
+            comm_w: instance.comm_w + Fr::ONE,
+            ..instance.clone()
+        };
+        assert_eq!(
+            is_satisfied(&ck, &r1cs, &tampered, &witness),
+            Err(Unsatisfied::CommitmentToW)
+        );
+    }
+
+    #[test]
+    fn verifier_folds_to_the_provers_instance() {
+        let (ck, r1cs, running, running_witness) = folded_three_inputs(2);
+        let step = LinearStep::<Fr>::three_inputs();
+        let adder = HashMap::from([("adder".to_string(), json!(1))]);
+        let step_in = [Fr::from(1), Fr::from(2), Fr::from(3)];
+        let (fresh, fresh_witness) =
+            RelaxedR1CSWitness::from_circom(&ck, &r1cs, &step.witness(&step_in, &adder));
+
+        let (folded, folded_witness, comm_t) = fold(
+            &ck,
+            &r1cs,
+            (&running, &running_witness),
+            (&fresh, &fresh_witness),
+        );
+
+        assert_eq!(running.fold(&fresh, comm_t), folded);
+        assert_eq!(is_satisfied(&ck, &r1cs, &folded, &folded_witness), Ok(()));
+    }
+}
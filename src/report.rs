@@ -0,0 +1,45 @@
+use std::fmt;
+
+use nova_scotia::F;
+use nova_snark::traits::Group;
+use serde::Serialize;
+
+use crate::trace::FoldingTrace;
+
+/// What a run produced, returned by every `run_*` function.
+pub struct RunReport<G1: Group, G2: Group> {
+    pub z_last: Vec<F<G1>>,
+    /// z_n of the secondary circuit. It is the trivial circuit, so this is just its z0.
+    pub z_last_secondary: Vec<F<G2>>,
+    pub trace: FoldingTrace,
+    pub sizes: ProofSizes,
+}
+
+/*
+Sizes in bytes of everything that might be shipped over the wire, serialized with bincode (the
+same encoding nova uses internally). The prover and verifier keys are the ones of the compression
+backend used for the run.
+*/
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ProofSizes {
+    pub public_params: usize,
+    pub recursive_snark: usize,
+    pub compressed_snark: usize,
+    pub prover_key: usize,
+    pub verifier_key: usize,
+}
+
+impl fmt::Display for ProofSizes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Serialized sizes:")?;
+        writeln!(f, "    PublicParams:    {} bytes", self.public_params)?;
+        writeln!(f, "    RecursiveSNARK:  {} bytes", self.recursive_snark)?;
+        writeln!(f, "    CompressedSNARK: {} bytes", self.compressed_snark)?;
+        writeln!(f, "    ProverKey:       {} bytes", self.prover_key)?;
+        write!(f, "    VerifierKey:     {} bytes", self.verifier_key)
+    }
+}
+
+pub fn serialized_size<T: Serialize>(value: &T) -> usize {
+    bincode::serialized_size(value).unwrap() as usize
+}
@@ -0,0 +1,73 @@
+use std::{fs, path::Path, time::Duration};
+
+use serde::Serialize;
+
+/*
+Per step profile of a folding run. Witness generation happens before folding starts (see
+pipeline::WitnessGeneration), so the two timings of a step are measured separately. With parallel
+witness generation the witness time is the wall time of that step's task on the thread pool.
+*/
+#[derive(Clone, Debug, Serialize)]
+pub struct StepRecord {
+    pub step: usize,
+    pub witness_generation_us: u128,
+    pub fold_us: u128,
+    /// Resident set size of the process right after the step was folded, None where /proc is not
+    /// available.
+    pub resident_memory_bytes: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FoldingTrace {
+    pub steps: Vec<StepRecord>,
+}
+
+impl FoldingTrace {
+    pub fn record(&mut self, witness_generation: Duration, fold: Duration) {
+        self.steps.push(StepRecord {
+            step: self.steps.len(),
+            witness_generation_us: witness_generation.as_micros(),
+            fold_us: fold.as_micros(),
+            resident_memory_bytes: resident_memory_bytes(),
+        });
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = "step,witness_generation_us,fold_us,resident_memory_bytes\n".to_string();
+        for record in &self.steps {
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                record.step,
+                record.witness_generation_us,
+                record.fold_us,
+                record
+                    .resident_memory_bytes
+                    .map(|bytes| bytes.to_string())
+                    .unwrap_or_default()
+            ));
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn write_csv(&self, path: impl AsRef<Path>) {
+        fs::write(path, self.to_csv()).unwrap();
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) {
+        fs::write(path, self.to_json()).unwrap();
+    }
+}
+
+/// Reads VmRSS from /proc/self/status (Linux only).
+pub fn resident_memory_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+
+    Some(kilobytes * 1024)
+}
@@ -0,0 +1,73 @@
+use std::{fs, path::Path};
+
+use ff::PrimeField;
+use nova_scotia::circom::circuit::CircomCircuit;
+use serde::Serialize;
+
+use crate::{field, pipeline::StepCircuits};
+
+/*
+Every state of a run, z_0 (the start public input) through z_n (what verify returns), as decimal
+strings. Nova only hands back z_n, but each step_out of a circom circuit sits in its witness, right
+after the constant one, so the whole sequence can be read off the step circuits that were folded.
+*/
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Trajectory {
+    pub states: Vec<Vec<String>>,
+}
+
+impl Trajectory {
+    pub fn from_circuits<Fr: PrimeField>(
+        start_public_input: &[Fr],
+        step_circuits: &StepCircuits<CircomCircuit<Fr>>,
+    ) -> Self {
+        let mut trajectory = Trajectory::default();
+        trajectory.push(start_public_input);
+        for circuit in &step_circuits.circuits {
+            trajectory.push(&circuit.get_public_outputs());
+        }
+
+        trajectory
+    }
+
+    pub fn push<Fr: PrimeField>(&mut self, z: &[Fr]) {
+        self.states
+            .push(z.iter().map(field::to_decimal_string).collect());
+    }
+
+    /// z_i, parsed back into field elements.
+    pub fn state<Fr: PrimeField>(&self, i: usize) -> Vec<Fr> {
+        self.states[i]
+            .iter()
+            .map(|x| field::from_str(x).unwrap())
+            .collect()
+    }
+
+    /// `step,z[0],z[1],...`, one row per state starting at step 0 for z_0.
+    pub fn to_csv(&self) -> String {
+        let arity = self.states.first().map_or(0, |z| z.len());
+        let mut csv = "step".to_string();
+        for i in 0..arity {
+            csv.push_str(&format!(",z[{}]", i));
+        }
+        csv.push('\n');
+
+        for (step, z) in self.states.iter().enumerate() {
+            csv.push_str(&format!("{},{}\n", step, z.join(",")));
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn write_csv(&self, path: impl AsRef<Path>) {
+        fs::write(path, self.to_csv()).unwrap();
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) {
+        fs::write(path, self.to_json()).unwrap();
+    }
+}
@@ -0,0 +1,79 @@
+use bellperson::{gadgets::num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use nova_snark::traits::circuit::StepCircuit;
+
+use crate::pipeline::StepCircuits;
+
+/*
+Runs k logical steps of a circuit in a single Nova iteration:
+
+    step_out = C_k(... C_2(C_1(step_in)))
+
+Every iteration of the folding carries the cost of the augmented (verifier) circuit, around ten
+thousand constraints, which is most of the work for circuits as small as 2inputs. Unrolling pays
+it once per k steps, at the price of a k times bigger step circuit. The public params depend on k,
+and the number of logical steps has to be a multiple of it.
+*/
+#[derive(Clone)]
+pub struct Unrolled<C> {
+    pub steps: Vec<C>,
+}
+
+impl<C> Unrolled<C> {
+    pub fn new(steps: Vec<C>) -> Self {
+        assert!(!steps.is_empty(), "cannot unroll zero steps");
+
+        Unrolled { steps }
+    }
+
+    /// k copies of a circuit without a witness, for `PublicParams::setup`.
+    pub fn setup_circuit(circuit: C, k: usize) -> Self
+    where
+        C: Clone,
+    {
+        Unrolled::new(vec![circuit; k])
+    }
+}
+
+impl<Fr: PrimeField, C: StepCircuit<Fr>> StepCircuit<Fr> for Unrolled<C> {
+    fn arity(&self) -> usize {
+        self.steps[0].arity()
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let mut z = z.to_vec();
+        for (i, step) in self.steps.iter().enumerate() {
+            z = step.synthesize(&mut cs.namespace(|| format!("step {}", i)), &z)?;
+        }
+
+        Ok(z)
+    }
+}
+
+/// Groups every k consecutive steps into one. The witness time of a group is the sum of its steps.
+pub fn unroll<C>(step_circuits: StepCircuits<C>, k: usize) -> StepCircuits<Unrolled<C>> {
+    let steps = step_circuits.circuits.len();
+    assert!(
+        k > 0 && steps % k == 0,
+        "{} steps cannot be unrolled by {}",
+        steps,
+        k
+    );
+
+    let mut circuits = Vec::with_capacity(steps / k);
+    let mut witness_times = Vec::with_capacity(steps / k);
+    let mut steps = step_circuits.circuits.into_iter();
+    for times in step_circuits.witness_times.chunks(k) {
+        circuits.push(Unrolled::new(steps.by_ref().take(k).collect()));
+        witness_times.push(times.iter().sum());
+    }
+
+    StepCircuits {
+        circuits,
+        witness_times,
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use ff::PrimeField;
+use nova_scotia::circom::reader::load_witness_from_file;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    field,
+    inputs::{to_circom_input, SignalShapes},
+};
+
+/*
+circom can emit two kinds of witness generators for the same circuit:
+
+    circom circuit.circom --r1cs --wasm    ->  circuit_js/circuit.wasm (run through node)
+    circom circuit.circom --r1cs --c       ->  circuit_cpp/ (run `make` to get a native binary)
+
+The native binary is much faster for large circuits. Which one to run is decided from the file
+name, anything that is not a `.wasm` file is executed as a native binary.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WitnessGeneratorKind {
+    Wasm,
+    Native,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessGenerator {
+    pub kind: WitnessGeneratorKind,
+    pub path: PathBuf,
+}
+
+impl WitnessGenerator {
+    /// Selects the generator kind from the extension of `path`, the same rule nova-scotia uses.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let kind = match path.extension() {
+            Some(extension) if extension == "wasm" => WitnessGeneratorKind::Wasm,
+            _ => WitnessGeneratorKind::Native,
+        };
+
+        WitnessGenerator { kind, path }
+    }
+}
+
+/*
+A `manifest.json` sitting next to the artifacts of a circuit. Paths are relative to the manifest,
+`native` is optional since the C++ generator has to be compiled locally, `sym` is only needed to
+validate private inputs against the signals of the circuit.
+
+    {
+        "r1cs": "3inputs.r1cs",
+        "wasm": "3inputs.wasm",
+        "native": "3inputs_cpp/3inputs",
+        "sym": "3inputs.sym"
+    }
+*/
+#[derive(Clone, Debug, Deserialize)]
+pub struct CircuitManifest {
+    pub r1cs: PathBuf,
+    pub wasm: PathBuf,
+    pub native: Option<PathBuf>,
+    pub sym: Option<PathBuf>,
+}
+
+impl CircuitManifest {
+    /// Reads the manifest and resolves every path against the directory it lives in.
+    pub fn load(manifest_path: impl AsRef<Path>) -> Self {
+        let manifest_path = manifest_path.as_ref();
+        let manifest = fs::read_to_string(manifest_path).unwrap();
+        let manifest: CircuitManifest = serde_json::from_str(&manifest).unwrap();
+
+        let dir = manifest_path.parent().unwrap();
+        CircuitManifest {
+            r1cs: dir.join(manifest.r1cs),
+            wasm: dir.join(manifest.wasm),
+            native: manifest.native.map(|native| dir.join(native)),
+            sym: manifest.sym.map(|sym| dir.join(sym)),
+        }
+    }
+
+    pub fn witness_generator(&self, kind: WitnessGeneratorKind) -> Option<WitnessGenerator> {
+        let path = match kind {
+            WitnessGeneratorKind::Wasm => self.wasm.clone(),
+            WitnessGeneratorKind::Native => self.native.clone()?,
+        };
+
+        Some(WitnessGenerator { kind, path })
+    }
+
+    pub fn signal_shapes(&self) -> Option<SignalShapes> {
+        self.sym
+            .as_ref()
+            .map(|sym| SignalShapes::load(sym, &self.r1cs))
+    }
+}
+
+/// Runs the witness generator for a single step. Input and output files are named after `tag`
+/// inside `work_dir`, so several steps can be computed at the same time without clobbering each
+/// other (nova-scotia always uses `circom_input.json` in the current directory).
+pub fn compute_witness<Fr: PrimeField>(
+    witness_generator: &WitnessGenerator,
+    step_in: &[Fr],
+    private_input: &HashMap<String, Value>,
+    work_dir: &Path,
+    tag: &str,
+) -> Vec<Fr> {
+    let mut input: HashMap<String, Value> = private_input
+        .iter()
+        .map(|(signal, value)| (signal.clone(), to_circom_input::<Fr>(value).unwrap()))
+        .collect();
+    let step_in: Vec<Value> = step_in.iter().map(field::to_json).collect();
+    input.insert("step_in".to_string(), json!(step_in));
+
+    let input_file = work_dir.join(format!("{}.json", tag));
+    let output_file = work_dir.join(format!("{}.wtns", tag));
+    fs::write(&input_file, serde_json::to_string(&input).unwrap()).unwrap();
+
+    let output = match witness_generator.kind {
+        WitnessGeneratorKind::Wasm => {
+            let witness_js = witness_generator
+                .path
+                .parent()
+                .unwrap()
+                .join("generate_witness.js");
+            Command::new("node")
+                .arg(witness_js)
+                .arg(&witness_generator.path)
+                .arg(&input_file)
+                .arg(&output_file)
+                .output()
+        }
+        WitnessGeneratorKind::Native => Command::new(&witness_generator.path)
+            .arg(&input_file)
+            .arg(&output_file)
+            .output(),
+    }
+    .expect("failed to execute the witness generator");
+    assert!(
+        output.status.success(),
+        "witness generation failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let witness = load_witness_from_file(&output_file);
+    let _ = fs::remove_file(input_file);
+    let _ = fs::remove_file(output_file);
+
+    witness
+}